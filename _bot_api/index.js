@@ -137,40 +137,19 @@ async function spawnClient(roomCode, gameData) {
     };
     bot_engine.engine.stdout.on("data", data => {
         data = JSON.parse(data.toString().trim());
-        bot_engine.keyInfo.allKeys = [];
-        let frame = 0;
-        for (const key of data) {
-            let trueKey = {
-                TapLeft: "moveLeft",
-                TapRight: "moveRight",
-                DASLeft: "moveLeft",
-                DASRight: "moveRight",
-                Softdrop: "softDrop",
-                Hold: "hold",
-                RotateCW: "rotateCW",
-                RotateCCW: "rotateCCW",
-                Rotate180: "rotate180",
-                Harddrop: "hardDrop"
-            }[key];
-            bot_engine.keyInfo.allKeys.push({ type: "keydown", key: trueKey, frame });
-            if (settings.finesse == "human" && (key == "DASLeft" || key == "DASRight")) {
-                frame += client.handling.das + 1;
-            } else if (key == "Harddrop") {
-                frame += 1;
-            } else {
-                frame += 2;
-            }
-            bot_engine.keyInfo.allKeys.push({ type: "keyup", key: trueKey, frame });
+        if (data.error) {
+            // the engine is still alive, it just couldn't find keys for this piece. drop it where it is and move on
+            console.error(`keygen: ${data.error.kind}: ${data.error.message}`);
+            data = { keys: [
+                { type: "keydown", frame: 0, data: { key: "hardDrop", subframe: 0 } },
+                { type: "keyup", frame: 0, data: { key: "hardDrop", subframe: 0 } }
+            ], location: {} };
         }
+        // tetr.io key events, timed by keygen for our handling and pps, and where they put the piece in tetr.io's coordinates
+        bot_engine.keyInfo.allKeys = data.keys;
         bot_engine.keyInfo.length = data.keys.length;
+        bot_engine.keyInfo.desiredLocation = data.location;
         bot_engine.keyInfo.sendingStdin = false;
-        bot_engine.startedMove = undefined;
-        // bot_engine.keyInfo.desiredLocation = {
-        //     piece: data.desired_location.piece,
-        //     x: data.desired_location.x,
-        //     y: data.desired_location.y,
-        //     rotation: {"Up": 0, "Right": 1, "Down": 2, "Left": 3}[data.desired_location.rotation]
-        // };
     });
 
     bot_engine.engine.stderr.on("data", data => {
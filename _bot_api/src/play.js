@@ -4,6 +4,27 @@ dotenv.config({ path: "../.env", quiet: true });
 
 // moveLeft, moveRight, softDrop, hardDrop, hold, rotateCW, rotateCCW, rotate180
 
+// the state the bot's search sees for a player
+function toGame(engine) {
+    let board = [0,0,0,0,0,0,0,0,0,0];
+    for (let row = 0; row < 20; row++) {
+        for (let col = 0; col < 10; col++) {
+            board[col] += +!!engine.board.state[row][col] * (1 << row);
+        }
+    }
+    return {
+        board: { cols: board },
+        hold: engine.held?.toUpperCase(),
+        b2b: engine.stats.b2b,
+        combo: engine.stats.combo,
+        incoming_garbage: engine.garbageQueue.queue.map(x => x.amount).reduce((a,b) => a+b, 0)
+    };
+}
+
+function toQueue(engine) {
+    return [engine.falling.symbol].concat(engine.queue.value).map(x => x.toUpperCase());
+}
+
 export async function handlePlay(data, client, room, settings, gameData, logFuncs, bot_engine) {
     bot_engine.keyInfo = {
         sendingStdin: false,
@@ -41,112 +62,45 @@ export async function handlePlay(data, client, room, settings, gameData, logFunc
             bot_engine.keyInfo.startFrame = dt.frame;
             bot_engine.keyInfo.sendingStdin = true;
 
-            let board_new = [0,0,0,0,0,0,0,0,0,0];
-            for (let row = 0; row < 20; row++) {
-                for (let col = 0; col < 10; col++) {
-                    board_new[col] += +!!engine.board.state[row][col] * (1 << row);
-                }
-            }
-
-            let queue = [engine.falling.symbol].concat(engine.queue.value).map(x => x.toUpperCase());
-            let hold = engine.held?.toUpperCase();
-
             let depth = 10;
 
             let input = {
-                game: {
-                    board: { cols: board_new },
-                    hold,
-                    b2b: engine.stats.b2b,
-                    combo: engine.stats.combo,
-                    incoming_garbage: engine.garbageQueue.queue.map(x => x.amount).reduce((a,b) => a+b, 0)
-                },
-                queue,
+                game: toGame(engine),
+                queue: toQueue(engine),
                 beam_width: Math.floor(200000 / (depth * settings.pps)),
                 beam_depth: depth,
-                human: settings.finesse == "human"
+                human: settings.finesse == "human",
+                tetrio_handling: {
+                    das: client.handling.das,
+                    arr: client.handling.arr,
+                    sdf: client.handling.sdf,
+                    dcd: client.handling.dcd,
+                    g: room.options.g
+                },
+                pps: settings.pps,
+                opponent: opponentEngine && { game: toGame(opponentEngine), queue: toQueue(opponentEngine) }
             };
 
             bot_engine.engine.stdin.write(JSON.stringify(input) + "\n");
         }
 
-        let allKeys = bot_engine.keyInfo.allKeys;
-
-        if (settings.finesse == "human") {
-            if (allKeys.length != 0 && dt.frame - bot_engine.keyInfo.startFrame > 60 / settings.pps - allKeys[allKeys.length - 1].frame - 2.3) {
-                if (!bot_engine.startedMove) {
-                    bot_engine.startedMove = dt.frame;
-                }
-                if (!allKeys.some(x => dt.frame - bot_engine.startedMove >= x.frame)) { return {}; }
-                let key = allKeys.shift();
-                return { keys: [{
-                    type: key.type,
-                    frame: dt.frame,
-                    data: {
-                        key: key.key,
-                        subframe: 0
-                    }
-                }] };
-            }
-        } else if (settings.finesse == "inhuman") {
-            if (allKeys.length != 0 && dt.frame - bot_engine.keyInfo.startFrame > 60 / settings.pps - allKeys[allKeys.length - 1].frame - 2.3) {
-                let key = bot_engine.keyInfo.allKeys.shift();
-                return { keys: [{
-                    type: key.type,
-                    frame: dt.frame,
-                    data: {
-                        key: key.key,
-                        subframe: 0
-                    }
-                }] };
-            }
-        } else {
-            if (allKeys.length != 0 && dt.frame - bot_engine.keyInfo.startFrame > 60 / settings.pps - 1.3) {
-                let keys = [];
-                let sfr = 0;
-                for (const key of allKeys) {
-                    if (key.type == "keyup" && key.key == "softDrop") sfr += 0.1;
-                    keys.push({
-                        type: key.type,
-                        frame: dt.frame,
-                        data: {
-                            key: key.key,
-                            subframe: sfr
-                        }
-                    });
-                }
-                bot_engine.keyInfo.allKeys = [];
-                bot_engine.keyInfo.length = 0;
-                return { keys };
-            }
+        // keygen's events are in frames from when we asked for the piece, already spaced out for our handling and pps, so each
+        // one goes out on its frame. any that are late because the search took longer go out straight away, in order
+        const { allKeys, startFrame } = bot_engine.keyInfo;
+        const due = allKeys.filter(key => startFrame + key.frame <= dt.frame);
+        if (due.length != 0) {
+            bot_engine.keyInfo.allKeys = allKeys.slice(due.length);
+            return { keys: due.map(key => ({
+                ...key,
+                frame: dt.frame,
+                data: { ...key.data, subframe: startFrame + key.frame == dt.frame ? key.data.subframe : 0 }
+            })) };
         }
 
         return {};
     });
 }
 
-// my movegen defines a location as the center of the piece
-// tetrio defines it as the top left corner
-// this function shifts from my movegen to tetrio's version
-function centerToCorner(pieceLocation) {
-    let xOff, yOff;
-    if (pieceLocation.piece == "I") {
-        xOff = [-1,-2,-2,-1][pieceLocation.rotation];
-        yOff = [1,1,2,2][pieceLocation.rotation];
-    } else if (pieceLocation.piece == "O") {
-        xOff = [0,0,-1,-1][pieceLocation.rotation];
-        yOff = [1,0,0,1][pieceLocation.rotation];
-    } else {
-        xOff = -1;
-        yOff = 1;
-    }
-    return {
-        ...pieceLocation,
-        x: pieceLocation.x + xOff,
-        y: pieceLocation.y + yOff,
-    }
-}
-
 function keydown(key, frame, subframe = 0) {
     return {
         frame: frame,
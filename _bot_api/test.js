@@ -26,13 +26,9 @@ const botEngine = {
 };
 
 botEngine.engine.stdout.on("data", data => {
-    botEngine.keyInfo.allKeys = JSON.parse(data.toString().trim());
-    // botEngine.keyInfo.desiredLocation = {
-    //     piece: data.desired_location.piece,
-    //     x: data.desired_location.x,
-    //     y: data.desired_location.y,
-    //     rotation: { "North": 0, "East": 1, "South": 2, "West": 3 }[data.desired_location.rotation]
-    // };
+    const reply = JSON.parse(data.toString().trim());
+    botEngine.keyInfo.allKeys = reply.keys;
+    botEngine.keyInfo.desiredLocation = reply.location;
 });
 
 botEngine.engine.stderr.on("data", data => {
@@ -0,0 +1,32 @@
+use std::hint::black_box;
+use criterion::{criterion_group, criterion_main, Criterion};
+use utils::{
+    data::{Board, Piece, PieceLocation},
+    game::GameConfig,
+    movegen::movegen_piece
+};
+
+const PIECES: [Piece; 7] = [Piece::I, Piece::O, Piece::T, Piece::L, Piece::J, Piece::S, Piece::Z];
+
+// every piece on an empty board, a low messy one with overhangs and a tall one with tucks
+fn criterion_benchmark(c: &mut Criterion) {
+    let boards = [
+        ("empty", Board { cols: [0; 10] }),
+        ("low", Board { cols: [1,0,77,112,19,16,113,192,0,12] }),
+        ("tall", Board { cols: [6665,64,119,3608,0,18,703,9,4224,16583] })
+    ];
+    for (name, board) in boards {
+        let config = GameConfig::default();
+        let mut arena: Vec<PieceLocation> = Vec::with_capacity(1024);
+        c.bench_function(&format!("movegen {name}"), |b| b.iter(|| {
+            arena.clear();
+            for piece in PIECES {
+                movegen_piece(&mut arena, black_box(&board), piece, true, &config);
+            }
+            black_box(arena.len())
+        }));
+    }
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
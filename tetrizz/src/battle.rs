@@ -1,6 +1,6 @@
 use utils::{
     data::{Piece, PieceLocation},
-    game::Game
+    game::{Game, PlacementInfo}
 };
 use crate::{
     eval::base::Eval,
@@ -10,33 +10,81 @@ use crate::{
 pub struct Player<T: Eval> {
     pub game: Game,
     pub queue: Vec<Piece>,
-    pub eval: T
+    pub eval: T,
+    pub depth: usize,
+    pub width: usize
 }
 
-pub struct Battle<T: Eval, U: Eval> {
-    pub player0: Player<T>,
-    pub player1: Player<U>,
-    pub who: u8
+impl<T: Eval> Player<T> {
+    pub fn new(eval: T) -> Self {
+        Self { game: Game::new_empty(), queue: vec![], eval, depth: 6, width: 5000 }
+    }
+
+    // plays the next piece, taking it off the queue along with the one after it if it went into an empty hold. the
+    // caller keeps the queue filled. None if the queue is empty or search finds nothing
+    pub fn play(&mut self) -> Option<(PieceLocation, PlacementInfo)> {
+        let current = *self.queue.first()?;
+        let depth = self.depth.min(self.queue.len());
+        let found = search(&self.game, &self.queue, &self.eval, depth, self.width)?;
+        self.queue.remove(0);
+        if found.piece != current && self.game.hold.is_none() {
+            self.queue.remove(0);
+        }
+        let info = self.game.advance(current, &found);
+        Some((found, info))
+    }
+}
+
+// stands in for an opponent, deciding how much garbage comes in with each piece the player places. sources are open
+// loop: what they send doesn't depend on attack. the player's game already cancels its attack against the garbage
+// waiting in incoming_garbage, so attack is only what got past that
+pub trait GarbageSource {
+    // lines sent at the player while they placed a piece. attack is what the player sent with it
+    fn next(&mut self, attack: u16) -> u16;
+}
+
+// the other side of a battle: another bot, or a garbage source standing in for one. a source is much cheaper, and
+// scripted ones send the same garbage every time
+pub trait Opponent {
+    // answers a piece the player placed, which sent attack. the lines sent back, None if the opponent can't go on
+    fn respond(&mut self, attack: u16) -> Option<u16>;
 }
 
-pub fn advance_player<T: Eval, U: Eval>(player: &mut Player<T>, opponent: &mut Player<U>) -> Option<PieceLocation> {
-    let player_move = search(&player.game, &player.queue, &player.eval, 6, 5000);
-    if let Some(p) = player_move {
-        let info = player.game.advance(player.queue[0], &p);
-        opponent.game.incoming_garbage += info.outgoing_attack;
-        return Some(p);
+impl<G: GarbageSource> Opponent for G {
+    fn respond(&mut self, attack: u16) -> Option<u16> {
+        Some(self.next(attack))
     }
-    None
 }
 
-impl<T: Eval, U: Eval> Battle<T, U> {
-    pub fn advance(&mut self) -> Option<PieceLocation> {
-        if self.who == 0 {
-            self.who = 1;
-            return advance_player(&mut self.player0, &mut self.player1);
-        } else {
-            self.who = 0;
-            return advance_player(&mut self.player1, &mut self.player0);
-        }
+// takes the attack and plays a piece of its own
+impl<T: Eval> Opponent for Player<T> {
+    fn respond(&mut self, attack: u16) -> Option<u16> {
+        self.game.incoming_garbage = self.game.incoming_garbage.saturating_add(attack);
+        self.play().map(|(_, info)| info.outgoing_attack)
     }
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Player,
+    Opponent
+}
+
+pub struct Battle<T: Eval, O: Opponent> {
+    pub player: Player<T>,
+    pub opponent: O
+}
+
+impl<T: Eval, O: Opponent> Battle<T, O> {
+    pub fn new(player: Player<T>, opponent: O) -> Self {
+        Self { player, opponent }
+    }
+
+    // plays the player's next piece and the opponent's answer to it. Err is the side that couldn't go on
+    pub fn advance(&mut self) -> Result<(PieceLocation, PlacementInfo), Side> {
+        let (found, info) = self.player.play().ok_or(Side::Player)?;
+        let sent = self.opponent.respond(info.outgoing_attack).ok_or(Side::Opponent)?;
+        self.player.game.incoming_garbage = self.player.game.incoming_garbage.saturating_add(sent);
+        Ok((found, info))
+    }
+}
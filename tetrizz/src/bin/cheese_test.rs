@@ -1,39 +1,38 @@
-use utils::{
-    game::Game,
-    queue::extend_queue
-};
-use rand::Rng;
+use rayon::prelude::*;
 use tetrizz::{
-    search::search,
-    eval::base::MinimalEval
+    cheese::{CheeseRace, CheeseResult, run},
+    eval::downstack::DownstackEval
 };
 
+// usage: cheese_test [--seeds N] [--lines N] [--visible N] [--messiness F] [--depth N] [--width N]
+// plays a cheese race with the downstack eval for every seed from 0 and reports pieces per garbage line cleared
 fn main() {
-    let mut rng = rand::rng();
-    let mut game = Game::new_empty();
-    let mut last_col = rng.random_range(0..10);
-    for _ in 0..10 {
-        game.board.add_garbage(last_col, 1);
-        let c = rng.random_range(0..9);
-        last_col = if c >= last_col { c + 1 } else { c };
-    }
-    println!("{}", game.into_string(None));
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let arg = |name: &str| args.iter()
+        .position(|a| a == name)
+        .map(|i| args.get(i + 1).unwrap_or_else(|| panic!("{name} needs a value")));
+    let num = |name: &str, default: usize| arg(name).map_or(default, |v| v.parse().unwrap_or_else(|_| panic!("{name} needs a number")));
 
-    let eval = MinimalEval { values: [-0.3335903388433292, -0.25750971325030974, -0.05115233297009883, -0.3067533752401169, -0.1854127319664896, -0.1070342999530273, -0.3690213829655287, 0.09663015564572557, -0.4766250155906852, -0.05872828521759004, 1.06812123537880416, -1.0169857268318688, -0.4575993254187147, 2.28085733274743393, 0.002] };
-    let mut queue = vec![];
-    extend_queue(&mut queue, 5);
-    loop {
-        if queue.len() < 8 { extend_queue(&mut queue, 2) }
-        let found_loc = search(&game, &queue[..7].iter().copied().collect(), &eval, 6, 50000);
-        let found_loc = if let Some(s) = found_loc { s } else { break; };
-        println!("{}", game.into_string(Some(&found_loc)));
-        game.advance(queue[0], &found_loc);
-        queue.remove(0);
-        
-        if found_loc.blocks().iter().any(|(_, y)| *y < 10) {
-            game.board.add_garbage(last_col, 1);
-            let c = rng.random_range(0..9);
-            last_col = if c >= last_col { c + 1 } else { c };
-        }
+    let defaults = CheeseRace::default();
+    let race = CheeseRace {
+        lines: num("--lines", defaults.lines),
+        visible: num("--visible", defaults.visible),
+        messiness: arg("--messiness").map_or(defaults.messiness, |v| v.parse().expect("--messiness needs a number")),
+        depth: num("--depth", defaults.depth),
+        width: num("--width", defaults.width),
+        ..defaults
+    };
+    let seeds = num("--seeds", 20) as u64;
+    let eval = DownstackEval::default();
+
+    let results: Vec<CheeseResult> = (0..seeds).into_par_iter().map(|seed| run(&race, &eval, seed)).collect();
+    for r in &results {
+        let status = if r.finished { "done" } else { "topped out" };
+        println!("seed {:>4}: {:>4} pieces, {:>3}/{} lines, {:.3} pieces/line ({status})", r.seed, r.pieces, r.cleared, race.lines, r.pieces_per_line());
     }
-}
\ No newline at end of file
+
+    let pieces = results.iter().map(|r| r.pieces).sum::<usize>();
+    let cleared = results.iter().map(|r| r.cleared).sum::<usize>();
+    let finished = results.iter().filter(|r| r.finished).count();
+    println!("{finished}/{seeds} finished, {:.3} pieces/line overall", pieces as f64 / cleared.max(1) as f64);
+}
@@ -1,258 +1,127 @@
-use std::rc::Rc;
-use bitboard_traits::BitboardTrait;
 use utils::{
-    data::{Piece, Board, Rotation, PieceLocation, Spin, ROT},
+    data::Piece,
     game::Game,
-    movegen::{CollisionMap, bb, bb_low, kicks, kicks_180, SPAWN_ROW, SPAWN_COL}
+    input::{Finesse, Handling, MovementAction, keygen, timeline},
+    replay::ReplayEvent
 };
 use tetrizz::{
+    error::BotError,
     eval::base::MinimalEval,
-    search::search,
+    opener::Book,
+    pc::{first_pc, plausible},
+    search::{Opponent, search_vs},
 };
-use serde::{Serialize, Deserialize};
+use serde::Deserialize;
 
-#[derive(Debug, Copy, Clone, Serialize, PartialEq, Eq)]
-pub enum MovementAction {
-    Spawn,
-    TapLeft,
-    TapRight,
-    DASLeft,
-    DASRight,
-    Softdrop,
-    Hold,
-    RotateCW,
-    RotateCCW,
-    Rotate180,
-    Harddrop
+#[derive(Deserialize, Debug)]
+struct InObj {
+    game: Game,
+    queue: Vec<Piece>,
+    beam_width: usize,
+    beam_depth: usize,
+    human: bool,
+    // with handling, the keys are the fastest ones for it and a timeline of key events is printed instead of the list
+    // of actions
+    #[serde(default)]
+    handling: Option<Handling>,
+    // the same in tetr.io's units, as a client reads them from its settings. handling wins if both are given
+    #[serde(default)]
+    tetrio_handling: Option<TetrioHandling>,
+    #[serde(default)]
+    pps: Option<f32>,
+    // their expected attack over the next beam_depth pieces is taken into account
+    #[serde(default)]
+    opponent: Option<Opponent>
 }
 
-impl MovementAction {
-    pub fn move_left(cm: &[CollisionMap; 4], loc: &PieceLocation, n: i8) -> PieceLocation {
-        if n == 0 { return loc.clone() }
-        let new_loc = PieceLocation { x: loc.x - 1, y: loc.y, rotation: loc.rotation, piece: loc.piece, spin: Spin::None};
-        if cm[loc.rotation as usize].obstructed(new_loc.x, new_loc.y) { loc.clone() } 
-        else { Self::move_left(cm, &new_loc, n - 1) }
-    }
-    
-    pub fn move_right(cm: &[CollisionMap; 4], loc: &PieceLocation, n: i8) -> PieceLocation {
-        if n == 0 { return loc.clone() }
-        let new_loc = PieceLocation { x: loc.x + 1, y: loc.y, rotation: loc.rotation, piece: loc.piece, spin: Spin::None};
-        if cm[loc.rotation as usize].obstructed(new_loc.x, new_loc.y) { loc.clone() }
-        else { Self::move_right(cm, &new_loc, n - 1) }
-    }
-
-    pub fn drop_down(cm: &[CollisionMap; 4], loc: &PieceLocation) -> PieceLocation {
-        let new_y = 64 - (cm[loc.rotation as usize][loc.x as usize] & bb_low(loc.y + 1)).leading_zeros() as i8;
-        PieceLocation {
-            x: loc.x,
-            y: new_y,
-            piece: loc.piece,
-            rotation: loc.rotation,
-            spin: if loc.y == new_y { loc.spin } else { Spin::None }
-        }
-    }
+#[derive(Deserialize, Debug)]
+struct TetrioHandling {
+    das: f32,
+    arr: f32,
+    sdf: f32,
+    dcd: f32,
+    // the room's gravity, which tetr.io's sdf multiplies
+    #[serde(default)]
+    g: f32
+}
 
-    pub fn rotate(cm: &[CollisionMap; 4], fullspinmap: &[Board; 4], spinmap: &[Board; 4], immobile_spinmap: &[Board; 4], loc: &PieceLocation, to: Rotation) -> PieceLocation {
-        let cmr = &cm[to as usize];
-        let kcks = kicks(loc.piece, loc.rotation, to);
-        for i in 0..5 {
-            let (kx, ky) = kcks[i];
-            let (x, y) = (loc.x + kx, loc.y + ky);
-            if !cmr.obstructed(x, y) {
-                let spin = if loc.piece == Piece::T {
-                    if i >= 4 { Spin::Full }
-                    else {
-                        if fullspinmap[to as usize][x as usize] & bb(y) > 0 { Spin::Full }
-                        else if spinmap[to as usize][x as usize] & bb(y) > 0 { Spin::Mini }
-                        else { Spin::None }
-                    }
-                } else {
-                    if immobile_spinmap[to as usize][x as usize] & bb(y) > 0 { Spin::Mini }
-                    else { Spin::None }
-                };
-                return PieceLocation { x, y, piece: loc.piece, rotation: to, spin };
-            }
+// usage: keygen [--book FILE] [--pc HEIGHT]
+// one json input per line, one reply per line: {"keys", "location"}, or {"error": {"kind", "message"}} if the input can't
+// be handled. location is where the piece goes in tetr.io's corner coordinates (see PieceLocation::to_tetrio)
+// errors never stop the loop. openers in the book (the builtin one without --book) are played before searching, then
+// perfect clears up to HEIGHT lines (4 by default, 0 for none)
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let book = match args.iter().position(|a| a == "--book").map(|i| args.get(i + 1).expect("--book needs a file")) {
+        Some(path) => {
+            let text = std::fs::read_to_string(path).unwrap_or_else(|e| panic!("could not read {path}: {e}"));
+            Book::parse(&text).unwrap_or_else(|e| panic!("{path}: {e}"))
         }
-        loc.clone()
-    }
-
-    pub fn rotate_180(cm: &[CollisionMap; 4], fullspinmap: &[Board; 4], spinmap: &[Board; 4], immobile_spinmap: &[Board; 4], loc: &PieceLocation) -> PieceLocation {
-        let to = loc.rotation.rotate_180();
-        let cmr = &cm[to as usize];
-        let kcks = kicks_180(loc.piece, loc.rotation, to);
-        for i in 0..6 {
-            let (kx, ky) = kcks[i];
-            let (x, y) = (loc.x + kx, loc.y + ky);
-            if !cmr.obstructed(x, y) {
-                let spin = if loc.piece == Piece::T {
-                    if fullspinmap[to as usize][x as usize] & bb(y) > 0 { Spin::Full }
-                    else if spinmap[to as usize][x as usize] & bb(y) > 0 { Spin::Mini }
-                    else { Spin::None }
-                } else {
-                    if immobile_spinmap[to as usize][x as usize] & bb(y) > 0 { Spin::Mini }
-                    else { Spin::None }
-                };
-                return PieceLocation { x, y, piece: loc.piece, rotation: to, spin };
-            }
+        None => Book::builtin()
+    };
+    let pc_height = args.iter().position(|a| a == "--pc")
+        .map(|i| args.get(i + 1).and_then(|h| h.parse().ok()).expect("--pc needs a number"))
+        .unwrap_or(4);
+    for input in std::io::stdin().lines() {
+        let Ok(input) = input else { break };
+        match respond(&input, &book, pc_height) {
+            Ok(reply) => println!("{reply}\n"),
+            Err(e) => println!("{}\n", e.reply())
         }
-        loc.clone()
     }
 }
 
-#[derive(Debug, Clone)]
-struct Node {
-    parent_node: Option<Rc<Node>>,
-    action: MovementAction,
-    loc: PieceLocation,
-}
-
-pub fn keygen(board: &Board, loc: &PieceLocation, human: bool) -> Option<Vec<MovementAction>> {
-    let cm = ROT.map(|r| CollisionMap::new(board, loc.piece, r));
-    let mut searched_nodes: [[Board; 4]; 3] = std::array::from_fn(|_| std::array::from_fn(|_| Board::new()));
-    let mut to_search: Vec<Node> = vec![Node {
-        parent_node: None,
-        loc: PieceLocation { piece: loc.piece, x: SPAWN_COL as i8, y: SPAWN_ROW, rotation: Rotation::North, spin: Spin::None},
-        action: MovementAction::Spawn
-    }];
-
-    let mut fullspinmap: [Board; 4] = std::array::from_fn(|_| Board::new());
-    let mut spinmap: [Board; 4] = std::array::from_fn(|_| Board::new());
-    let mut immobile_spinmap: [Board; 4] = std::array::from_fn(|_| Board::new());
-
-    for x in 0..10 {
-        let c = [
-            if x > 0 { board[x - 1] >> 1 } else { !0 },
-            if x < 9 { board[x + 1] >> 1 } else { !0 },
-            if x < 9 { board[x + 1] << 1 | 1 } else { !0 },
-            if x > 0 { board[x - 1] << 1 | 1 } else { !0 }
-        ];
-
-        let spins = 
-            c[0] & c[1] & (c[2] | c[3]) | 
-            c[2] & c[3] & (c[0] | c[1]);
-
-        for rot in ROT {
-            if loc.piece == Piece::T {
-                spinmap[rot as usize][x] = spins;
-            }
-            if cm[rot as usize][x] != !0 {
-                if loc.piece == Piece::T {
-                    fullspinmap[rot as usize][x] = spins & c[rot as usize] & c[rot.rotate_cw() as usize];
-                }
-                immobile_spinmap[rot as usize][x] |= !cm[rot as usize][x] & (
-                    cm[rot as usize].cols.get(x - 1).copied().unwrap_or(!0)
-                    & cm[rot as usize].cols.get(x + 1).copied().unwrap_or(!0)
-                    & (cm[rot as usize][x] << 1 | 1)
-                    & cm[rot as usize][x] >> 1
-                );
-            }
-        }
+fn respond(input: &str, book: &Book, pc_height: usize) -> Result<String, BotError> {
+    let parsed: InObj = serde_json::from_str(input)?;
+    if parsed.queue.is_empty() {
+        return Err(BotError::EmptyQueue);
     }
 
-    let mut found_node: Option<Node> = None;
+    let eval = MinimalEval { values: [-333.5903388433292, -257.50971325030974, -51.15233297009883, -306.7533752401169, -185.4127319664896, -107.0342999530273, -369.0213829655287, 96.63015564572557, -476.6250155906852, -58.72828521759004, 1068.1212353788042, -1016.9857268318688, -457.5993254187147, 2280.857332747434, 0.0] };
 
-    while to_search.len() > 0 && found_node.is_none() {
-        let mut new_search: Vec<Node> = vec![];
-        for node in to_search.into_iter().map(Rc::new) {
-            let mut push_loc = |l: &PieceLocation, action: MovementAction| {
-                if found_node.is_some() { return; }
-                if cm[l.rotation as usize].obstructed(l.x, l.y) { return; }
-                let new_node = Node {
-                    loc: match action {
-                        MovementAction::TapLeft => MovementAction::move_left(&cm, l, 1),
-                        MovementAction::TapRight => MovementAction::move_right(&cm, l, 1),
-                        MovementAction::DASLeft => MovementAction::move_left(&cm, l, l.x),
-                        MovementAction::DASRight => MovementAction::move_right(&cm, l, 9 - l.x),
-                        MovementAction::Softdrop | MovementAction::Harddrop => MovementAction::drop_down(&cm, l),
-                        MovementAction::RotateCW => MovementAction::rotate(&cm, &fullspinmap, &spinmap, &immobile_spinmap, l, l.rotation.rotate_cw()),
-                        MovementAction::RotateCCW => MovementAction::rotate(&cm, &fullspinmap, &spinmap, &immobile_spinmap, l, l.rotation.rotate_ccw()),
-                        MovementAction::Rotate180 => MovementAction::rotate_180(&cm, &fullspinmap, &spinmap, &immobile_spinmap, l),
-                        _ => l.clone()
-                    },
-                    parent_node: Some(Rc::clone(&node)),
-                    action
-                };
-                let searched = &mut searched_nodes[new_node.loc.spin as usize][new_node.loc.rotation as usize][new_node.loc.x as usize];
-                if action == MovementAction::Harddrop {
-                    if new_node.loc.x == loc.x && new_node.loc.y == loc.y && new_node.loc.rotation == loc.rotation && new_node.loc.spin == loc.spin {
-                        found_node = Some(new_node);
-                    }
-                } else if *searched & bb(new_node.loc.y) == 0 {
-                    *searched |= bb(new_node.loc.y);
-                    new_search.push(new_node);
-                }
-            };
-            push_loc(&node.loc, MovementAction::Harddrop);
-            if human {
-                push_loc(&node.loc, MovementAction::DASLeft);
-                push_loc(&node.loc, MovementAction::DASRight);
-            }
-            push_loc(&node.loc, MovementAction::TapLeft);
-            push_loc(&node.loc, MovementAction::TapRight);
-            push_loc(&node.loc, MovementAction::Softdrop);
-            push_loc(&node.loc, MovementAction::RotateCW);
-            push_loc(&node.loc, MovementAction::RotateCCW);
-            push_loc(&node.loc, MovementAction::Rotate180);
+    let pc = || {
+        if !plausible(&parsed.game, &parsed.queue, pc_height) {
+            return None;
         }
-        to_search = new_search;
-    }
-
-    if found_node.is_none() {
-        return None;
+        first_pc(&parsed.game, &parsed.queue, pc_height)?.into_iter().next()
+    };
+    let found_move = book.next_move(&parsed.game, &parsed.queue).or_else(pc).or_else(|| search_vs(
+        &parsed.game,
+        &parsed.queue,
+        &eval,
+        parsed.beam_depth.min(parsed.queue.len()),
+        parsed.beam_width,
+        parsed.opponent.as_ref(),
+    )).ok_or(BotError::NoMove)?;
+
+    let handling = parsed.handling.or(parsed.tetrio_handling.map(|h| Handling::from_tetrio(h.das, h.arr, h.sdf, h.dcd, h.g)));
+    let finesse = match handling {
+        Some(handling) => Finesse::Frames(handling),
+        None if parsed.human => Finesse::Keys,
+        None => Finesse::Taps
+    };
+    let mut keys = keygen(&parsed.game.board, &found_move, &finesse, &parsed.game.config)?;
+
+    if found_move.piece != parsed.queue[0] {
+        keys.insert(0, MovementAction::Hold);
     }
 
-    let mut found_node = &Rc::new(found_node.unwrap());
-    let mut moves: Vec<MovementAction> = vec![];
-
-    while found_node.parent_node.is_some() {
-        moves.push(found_node.action.clone());
-        found_node = found_node.parent_node.as_ref().unwrap();
-    }
-
-    moves.reverse();
-    Some(moves)
-}
-
-#[derive(Deserialize, Debug)]
-struct InObj {
-    game: Game,
-    queue: Vec<Piece>,
-    beam_width: usize,
-    beam_depth: usize,
-    human: bool
-}
-
-fn main() {
-    loop {
-        let mut input = String::new();
-        std::io::stdin()
-            .read_line(&mut input)
-            .ok()
-            .expect("lol wtf is this");
-
-        let parsed: InObj = serde_json::from_str(&input).unwrap();
-
-        let eval = MinimalEval { values: [-333.5903388433292, -257.50971325030974, -51.15233297009883, -306.7533752401169, -185.4127319664896, -107.0342999530273, -369.0213829655287, 96.63015564572557, -476.6250155906852, -58.72828521759004, 1068.12123537880416, -1016.9857268318688, -457.5993254187147, 2280.85733274743393, 0.0] };
-
-        let found_move = search(
-            &parsed.game,
-            &parsed.queue.clone(),
-            &eval,
-            parsed.beam_depth,
-            parsed.beam_width,
-        );
-
-        let found_move = if let Some(m) = found_move { m } else {
-            println!("[\"Harddrop\", \"Harddrop\"]");
-            continue;
+    let (x, y) = found_move.to_tetrio();
+    let location = serde_json::json!({ "piece": found_move.piece, "x": x, "y": y, "rotation": found_move.rotation as u8 });
+    let Some(handling) = handling else {
+        return Ok(serde_json::json!({ "keys": keys, "location": location }).to_string());
+    };
+
+    // frames are from when the piece spawned, in tetr.io's key event format. keygen's keys always replay
+    let events = timeline(&parsed.game.board, found_move.piece, &keys, &parsed.game.config, &handling, parsed.pps)
+        .ok_or(BotError::IllegalMove(found_move))?;
+    let events: Vec<_> = events.iter().map(|e| {
+        let (kind, key) = match e.event {
+            ReplayEvent::KeyDown(k) => ("keydown", k),
+            ReplayEvent::KeyUp(k) => ("keyup", k),
+            ReplayEvent::Garbage { .. } => unreachable!()
         };
-
-        let mut keys = keygen(&parsed.game.board, &found_move, parsed.human).expect(&format!("Could not find keypresses!\nInput: {input}\nMove: {found_move:?}"));
-
-        if found_move.piece != parsed.queue[0] {
-            keys.insert(0, MovementAction::Hold);
-        }
-
-        println!("{}\n", serde_json::to_string(&keys).unwrap());
-    }
-}
\ No newline at end of file
+        serde_json::json!({ "type": kind, "frame": e.frame, "data": { "key": key.to_tetrio(), "subframe": e.subframe } })
+    }).collect();
+    Ok(serde_json::json!({ "keys": events, "location": location }).to_string())
+}
@@ -0,0 +1,52 @@
+use rayon::prelude::*;
+use tetrizz::modes::{Mode, ModeResult, ModeSettings, play};
+use utils::input::Finesse;
+
+// usage: mode_bench [sprint|blitz|zen|survival] [--seeds N] [--pps F] [--depth N] [--width N] [--pieces N] [--taps]
+//                   [--lines N] [--seconds F] [--apm F]
+// plays the mode for every seed from 0 with its own objective, and reports pieces, keys, finesse faults and the time
+// it would take at the given pps. --taps picks keys like a client that can't das, so finesse faults show up
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let arg = |name: &str| args.iter()
+        .position(|a| a == name)
+        .map(|i| args.get(i + 1).unwrap_or_else(|| panic!("{name} needs a value")));
+    let num = |name: &str, default: f32| arg(name).map_or(default, |v| v.parse().unwrap_or_else(|_| panic!("{name} needs a number")));
+
+    let mode = match args.first().map(String::as_str) {
+        None | Some("sprint") => Mode::Sprint { lines: num("--lines", 40.0) as u32 },
+        Some("blitz") => Mode::Blitz { seconds: num("--seconds", 120.0) },
+        Some("zen") => Mode::Zen,
+        Some("survival") => Mode::Survival { apm: num("--apm", 30.0) },
+        Some(other) if other.starts_with("--") => Mode::SPRINT,
+        Some(other) => panic!("unknown mode {other}")
+    };
+    let defaults = ModeSettings::default();
+    let settings = ModeSettings {
+        depth: num("--depth", defaults.depth as f32) as usize,
+        width: num("--width", defaults.width as f32) as usize,
+        pps: num("--pps", defaults.pps),
+        finesse: if args.iter().any(|a| a == "--taps") { Finesse::Taps } else { Finesse::Keys },
+        max_pieces: num("--pieces", defaults.max_pieces as f32) as usize
+    };
+    let seeds = num("--seeds", 10.0) as u64;
+    let eval = mode.eval();
+
+    println!("{mode:?} at {} pps", settings.pps);
+    let results: Vec<ModeResult> = (0..seeds).into_par_iter().map(|seed| play(&mode, &settings, &eval, seed)).collect();
+    for r in &results {
+        println!(
+            "seed {:>4}: {:>4} pieces, {:>3} lines, {:>7} points, {:>3} attack, {:.2} keys/piece, {:>3} faults, {:.1}s{}",
+            r.seed, r.pieces, r.lines, r.points, r.attack, r.keys_per_piece(), r.finesse_faults, r.time(settings.pps),
+            if r.topped_out { " (topped out)" } else { "" }
+        );
+    }
+
+    let n = results.len().max(1) as f32;
+    let mean = |f: &dyn Fn(&ModeResult) -> f32| results.iter().map(f).sum::<f32>() / n;
+    println!(
+        "mean: {:.1} pieces, {:.1} lines, {:.0} points, {:.2} keys/piece, {:.1} faults, {:.1}s, {} topped out",
+        mean(&|r| r.pieces as f32), mean(&|r| r.lines as f32), mean(&|r| r.points as f32), mean(&|r| r.keys_per_piece()),
+        mean(&|r| r.finesse_faults as f32), mean(&|r| r.time(settings.pps)), results.iter().filter(|r| r.topped_out).count()
+    );
+}
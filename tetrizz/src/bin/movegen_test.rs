@@ -1,13 +1,33 @@
 use utils::{
     data::{Board, Piece, PieceLocation},
-    movegen::movegen_piece
+    fumen::Fumen,
+    movegen::movegen_piece,
+    game::GameConfig
 };
 
+// usage: movegen_test [fumen]. the fumen's first page is used as the board, and its piece (if any) picks what to generate
 fn main() {
     // let board = Board { cols: [6665,64,119,3608,0,18,703,9,4224,16583] };
     // let board = Board { cols: [1,0,77,112,19,16,113,192,0,12] };
-    let board = Board { cols: [255,255,255,127,127,255,495,303,0,0] };
+    let (board, piece) = match std::env::args().nth(1) {
+        Some(s) => {
+            let fumen = Fumen::decode(&s).unwrap_or_else(|e| panic!("{e}"));
+            let page = &fumen.pages[0];
+            (page.board, page.piece.as_ref().map_or(Piece::I, |p| p.piece))
+        }
+        None => (Board::from_ascii("
+            ......##..
+            ###..##...
+            #######...
+            ########..
+            ######....
+            ########..
+            ########..
+            ########..
+            ########..
+        ").unwrap(), Piece::I)
+    };
     let mut arena: Vec<PieceLocation> = vec![];
-    movegen_piece(&mut arena, &board, Piece::I, true);
+    movegen_piece(&mut arena, &board, piece, true, &GameConfig::default());
     println!("{arena:?}");
-}
\ No newline at end of file
+}
@@ -1,36 +1,49 @@
 use utils::{
-    data::{Piece, PieceLocation},
+    data::Piece,
     game::Game,
-    movegen::movegen_piece
+    perft::{perft, perft_compare}
 };
 
-fn perft(arena: &mut Vec<PieceLocation>, game: &Game, queue: &[Piece; 7], idx: usize, depth: usize) -> usize {
-    // if depth > 2 { arena.clear(); }
-    let arena_idx = movegen_piece(arena, &game.board, queue[idx], true);
-    if depth == 1 {
-        return arena.len() - arena_idx;
-    }
-
-    let mut nodes = 0;
-    let moves: Vec<PieceLocation> = arena[arena_idx..].iter().cloned().collect();
-    for mv in moves {
-        let mut next_game = game.clone();
-        next_game.advance(queue[idx], &mv);
-        nodes += perft(arena, &next_game, queue, idx + 1, depth - 1);
-    }
-
-    nodes
-}
-
+// usage: perft [file] [--depth N] [--hold] [--compare]
+// the file is an ascii position (see utils::ascii) with a queue header. without one, the empty board and IOLJSZT are used.
+// --compare checks every movegen call against the brute force reference and stops at the first disagreement
 fn main() {
-    let mut arena: Vec<PieceLocation> = vec![];
-    let game = Game::new_empty();
-    let queue = [Piece::I, Piece::O, Piece::L, Piece::J, Piece::S, Piece::Z, Piece::T];
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let hold = args.iter().any(|a| a == "--hold");
+    let compare = args.iter().any(|a| a == "--compare");
+    let max_depth = args.iter()
+        .position(|a| a == "--depth")
+        .map(|i| args.get(i + 1).and_then(|d| d.parse().ok()).expect("--depth needs a number"))
+        .unwrap_or(7);
+    let path = args.iter()
+        .enumerate()
+        .find(|(i, a)| !a.starts_with("--") && (*i == 0 || args[i - 1] != "--depth"))
+        .map(|(_, a)| a);
 
-    for d in 1..=7 {
+    let (game, queue) = match path {
+        Some(path) => {
+            let text = std::fs::read_to_string(path).expect("could not read position file");
+            Game::from_ascii(&text).unwrap_or_else(|e| panic!("{e}"))
+        }
+        None => (Game::new_empty(), vec![Piece::I, Piece::O, Piece::L, Piece::J, Piece::S, Piece::Z, Piece::T])
+    };
+
+    for d in 1..=max_depth {
         let now = std::time::Instant::now();
-        let nodes = perft(&mut arena, &game, &queue, 0, d);
+        let nodes = if compare {
+            match perft_compare(&game, &queue, d, hold) {
+                Ok(n) => n,
+                Err(m) => {
+                    println!("mismatch for {:?} on\n{}", m.piece, m.board.to_ascii());
+                    println!("only in movegen: {:?}", m.extra);
+                    println!("only in reference: {:?}", m.missing);
+                    std::process::exit(1);
+                }
+            }
+        } else {
+            perft(&game, &queue, d, hold)
+        };
         let elapsed = now.elapsed().as_micros() as usize;
         println!("Depth: {d}  |  Nodes: {nodes}  |  Time: {}ms  |  NPS: {}", elapsed as f32 / 1000.0, nodes as f32 / (elapsed as f32 / 1000000.0));
     }
-}
\ No newline at end of file
+}
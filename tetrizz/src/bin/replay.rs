@@ -0,0 +1,53 @@
+use utils::replay::parse_replay;
+use tetrizz::{
+    search::search,
+    eval::base::MinimalEval
+};
+
+// usage: replay <file.ttr|file.ttrm> [--dump]
+// compares the bot's choice against every placement in the replay. with --dump, positions are printed as json lines instead
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let path = args.get(1).expect("usage: replay <file.ttr|file.ttrm> [--dump]");
+    let dump = args.iter().any(|a| a == "--dump");
+
+    let json = std::fs::read_to_string(path).expect("could not read replay file");
+    let players = match parse_replay(&json) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+
+    let eval = MinimalEval { values: [-333.5903388433292, -257.50971325030974, -51.15233297009883, -306.7533752401169, -185.4127319664896, -107.0342999530273, -369.0213829655287, 96.63015564572557, -476.6250155906852, -58.72828521759004, 1068.1212353788042, -1016.9857268318688, -457.5993254187147, 2280.857332747434, 0.0] };
+
+    for player in &players {
+        let positions = player.simulate();
+        if dump {
+            for p in &positions {
+                println!("{}", serde_json::to_string(p).unwrap());
+            }
+            continue;
+        }
+
+        let mut agreed = 0;
+        for p in &positions {
+            if let Some(found) = search(&p.game, &p.queue, &eval, p.queue.len().min(6), 2000) {
+                let mut ours = found.blocks();
+                let mut theirs = p.location.blocks();
+                ours.sort();
+                theirs.sort();
+                agreed += (ours == theirs) as usize;
+            }
+        }
+        println!(
+            "round {} | {} | placements: {} | agreed with bot: {} ({:.1}%)",
+            player.round,
+            player.username.as_deref().unwrap_or("?"),
+            positions.len(),
+            agreed,
+            100.0 * agreed as f64 / positions.len().max(1) as f64
+        );
+    }
+}
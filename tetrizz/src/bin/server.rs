@@ -0,0 +1,108 @@
+use std::{
+    net::TcpListener,
+    sync::Arc,
+    thread
+};
+
+use tetrizz::{
+    eval::base::MinimalEval,
+    server::{self, Rooms}
+};
+use tiny_http::{Header, Method, Response, Server};
+use tungstenite::{
+    Message,
+    handshake::server::ErrorResponse,
+    http::{Request, StatusCode}
+};
+
+// usage: server [--http ADDR] [--ws ADDR] [--threads N]
+// serves the api in tetrizz::server. rooms are picked by path:
+// - http: POST /rooms/<room> with a request as the body, GET /rooms/<room> for the state, DELETE /rooms/<room>
+// - websocket: connect to /rooms/<room>, then send one request per text message
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let arg = |name: &str, default: &str| args.iter()
+        .position(|a| a == name)
+        .map(|i| args.get(i + 1).unwrap_or_else(|| panic!("{name} needs a value")).clone())
+        .unwrap_or(default.to_string());
+    let threads: usize = arg("--threads", "4").parse().expect("--threads needs a number");
+
+    let eval = MinimalEval { values: [-333.5903388433292, -257.50971325030974, -51.15233297009883, -306.7533752401169, -185.4127319664896, -107.0342999530273, -369.0213829655287, 96.63015564572557, -476.6250155906852, -58.72828521759004, 1068.1212353788042, -1016.9857268318688, -457.5993254187147, 2280.857332747434, 0.0] };
+    let rooms = Arc::new(Rooms::new(eval));
+
+    let http_addr = arg("--http", "127.0.0.1:8080");
+    let http = Arc::new(Server::http(&http_addr).unwrap_or_else(|e| panic!("could not listen on {http_addr}: {e}")));
+    for _ in 0..threads {
+        let (http, rooms) = (http.clone(), rooms.clone());
+        thread::spawn(move || serve_http(&http, &rooms));
+    }
+    println!("http on {http_addr}");
+
+    let ws_addr = arg("--ws", "127.0.0.1:8081");
+    let listener = TcpListener::bind(&ws_addr).unwrap_or_else(|e| panic!("could not listen on {ws_addr}: {e}"));
+    println!("websocket on {ws_addr}");
+    for stream in listener.incoming().flatten() {
+        let rooms = rooms.clone();
+        thread::spawn(move || {
+            let mut room = String::new();
+            // the error type is tungstenite's
+            #[allow(clippy::result_large_err)]
+            let Ok(mut socket) = tungstenite::accept_hdr(stream, |req: &Request<()>, res| match room_of(req.uri().path()) {
+                Some(r) => {
+                    room = r.to_string();
+                    Ok(res)
+                }
+                None => {
+                    let mut err = ErrorResponse::new(Some("connect to /rooms/<room>".to_string()));
+                    *err.status_mut() = StatusCode::NOT_FOUND;
+                    Err(err)
+                }
+            }) else { return };
+            while let Ok(msg) = socket.read() {
+                let reply = match msg {
+                    Message::Text(text) => rooms.handle_json(&room, &text),
+                    Message::Close(_) => break,
+                    _ => continue
+                };
+                if socket.send(Message::text(reply)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+fn serve_http(http: &Server, rooms: &Rooms<MinimalEval>) {
+    let json = Header::from_bytes("Content-Type", "application/json").unwrap();
+    let cors = [
+        Header::from_bytes("Access-Control-Allow-Origin", "*").unwrap(),
+        Header::from_bytes("Access-Control-Allow-Methods", "GET, POST, DELETE, OPTIONS").unwrap(),
+        Header::from_bytes("Access-Control-Allow-Headers", "Content-Type").unwrap()
+    ];
+    for mut request in http.incoming_requests() {
+        let room = room_of(request.url()).map(str::to_string);
+        let (status, body) = match (request.method(), room) {
+            (Method::Options, _) => (204, String::new()),
+            (_, None) => (404, String::new()),
+            (Method::Get, Some(room)) => (200, serde_json::to_string(&rooms.handle(&room, server::Request::GetState)).unwrap()),
+            (Method::Delete, Some(room)) => (if rooms.remove(&room) { 204 } else { 404 }, String::new()),
+            (Method::Post, Some(room)) => {
+                let mut text = String::new();
+                match request.as_reader().read_to_string(&mut text) {
+                    Ok(_) => (200, rooms.handle_json(&room, &text)),
+                    Err(_) => (400, String::new())
+                }
+            }
+            _ => (405, String::new())
+        };
+        let mut response = Response::from_string(body).with_status_code(status).with_header(json.clone());
+        for header in &cors {
+            response.add_header(header.clone());
+        }
+        let _ = request.respond(response);
+    }
+}
+
+fn room_of(path: &str) -> Option<&str> {
+    path.strip_prefix("/rooms/").filter(|r| !r.is_empty() && !r.contains('/'))
+}
@@ -0,0 +1,145 @@
+use rayon::prelude::*;
+use serde::Serialize;
+use tetrizz::{
+    eval::{base::{Eval, MinimalEval}, downstack::DownstackEval},
+    garbage::{AttackLog, Pattern, Poisson, Scripted},
+    modes::ModeSettings,
+    survival::{SurvivalStats, survive}
+};
+use utils::replay::parse_replay;
+
+// usage: survival_bench [constant|burst|increasing|poisson|replay FILE] [--apm F] [--lines N] [--every F] [--growth F]
+//                       [--after N] [--attacks F] [--player N] [--seeds N] [--pps F] [--depth N] [--width N] [--pieces N]
+//                       [--eval minimal|downstack] [--format text|csv|json] [--out FILE]
+// plays every seed from 0 against the garbage and reports attack, survival, b2b, digging and spins. poisson sends --lines
+// at a time, --attacks times a minute on average; replay sends what a player in a tetr.io replay received (--player,
+// 0 by default). csv has a row per game; json has every game plus the totals, for tracking changes between versions
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+enum Source {
+    Pattern(Pattern),
+    Poisson { attacks_per_minute: f32, lines: u16 },
+    Log { replay: String, player: usize, #[serde(skip)] lines: Vec<u16> }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let arg = |name: &str| args.iter()
+        .position(|a| a == name)
+        .map(|i| args.get(i + 1).unwrap_or_else(|| panic!("{name} needs a value")));
+    let num = |name: &str, default: f32| arg(name).map_or(default, |v| v.parse().unwrap_or_else(|_| panic!("{name} needs a number")));
+
+    let apm = num("--apm", 30.0);
+    let pps = num("--pps", ModeSettings::default().pps);
+    let source = match args.first().map(String::as_str) {
+        Some("burst") => Source::Pattern(Pattern::Burst { lines: num("--lines", 8.0) as u16, every: num("--every", 15.0) }),
+        Some("increasing") => Source::Pattern(Pattern::Increasing { apm, growth: num("--growth", 0.001), after: num("--after", 200.0) as usize }),
+        Some("poisson") => Source::Poisson { attacks_per_minute: num("--attacks", 10.0), lines: num("--lines", 4.0) as u16 },
+        Some("replay") => {
+            let path = args.get(1).expect("replay needs a file");
+            let player = num("--player", 0.0) as usize;
+            let text = std::fs::read_to_string(path).unwrap_or_else(|e| panic!("could not read {path}: {e}"));
+            let replays = parse_replay(&text).unwrap_or_else(|e| panic!("{path}: {e}"));
+            let replay = replays.get(player).unwrap_or_else(|| panic!("{path} has no player {player}"));
+            Source::Log { replay: path.clone(), player, lines: AttackLog::from_replay(replay, pps).lines }
+        }
+        Some("constant") | None => Source::Pattern(Pattern::Constant { apm }),
+        Some(other) if other.starts_with("--") => Source::Pattern(Pattern::Constant { apm }),
+        Some(other) => panic!("unknown garbage source {other}")
+    };
+    let defaults = ModeSettings::default();
+    let settings = ModeSettings {
+        depth: num("--depth", defaults.depth as f32) as usize,
+        width: num("--width", defaults.width as f32) as usize,
+        pps,
+        max_pieces: num("--pieces", defaults.max_pieces as f32) as usize,
+        ..defaults
+    };
+    let seeds = num("--seeds", 10.0) as u64;
+    let games = match arg("--eval").map_or("minimal", String::as_str) {
+        "minimal" => play(&source, &settings, seeds, &MinimalEval { values: [-333.5903388433292, -257.50971325030974, -51.15233297009883, -306.7533752401169, -185.4127319664896, -107.0342999530273, -369.0213829655287, 96.63015564572557, -476.6250155906852, -58.72828521759004, 1068.1212353788042, -1016.9857268318688, -457.5993254187147, 2280.857332747434, 0.0] }),
+        "downstack" => play(&source, &settings, seeds, &DownstackEval::default()),
+        other => panic!("unknown eval {other}")
+    };
+    let total = totals(&games);
+
+    let out = match arg("--format").map_or("text", String::as_str) {
+        "csv" => {
+            let mut lines = vec![SurvivalStats::CSV_HEADER.to_string()];
+            lines.extend(games.iter().map(|g| g.csv_row(pps)));
+            lines.join("\n")
+        }
+        "json" => serde_json::to_string_pretty(&serde_json::json!({
+            "source": source,
+            "pps": pps,
+            "depth": settings.depth,
+            "width": settings.width,
+            "games": games,
+            "total": {
+                "pieces": total.pieces,
+                "seconds": total.time(pps),
+                "app": total.app(),
+                "apm": total.apm(pps),
+                "pieces_per_garbage": total.pieces_per_garbage(),
+                "spins": total.spins,
+                "minis": total.minis,
+                "quads": total.quads,
+                "pcs": total.pcs,
+                "b2b_chains": histogram(&total.b2b_chains),
+                "topped_out": games.iter().filter(|g| g.topped_out).count()
+            }
+        })).unwrap(),
+        "text" => {
+            let mut lines: Vec<String> = games.iter().map(|g| format!(
+                "seed {:>4}: {:>4} pieces ({:.1}s), {:.3} app, {:.1} apm, {:.2} pieces/garbage line, {} spins, {} quads, max b2b {}{}",
+                g.seed, g.pieces, g.time(pps), g.app(), g.apm(pps), g.pieces_per_garbage(), g.spins, g.quads, g.max_b2b(),
+                if g.topped_out { " (topped out)" } else { "" }
+            )).collect();
+            lines.push(format!(
+                "{} at {pps} pps: {:.1}s survived on average, {:.3} app, {:.1} apm, {:.2} pieces/garbage line",
+                serde_json::to_string(&source).unwrap(), total.time(pps) / games.len().max(1) as f32, total.app(), total.apm(pps), total.pieces_per_garbage()
+            ));
+            lines.push(format!("b2b chains (length: count): {:?}", histogram(&total.b2b_chains)));
+            lines.join("\n")
+        }
+        other => panic!("unknown format {other}")
+    };
+    match arg("--out") {
+        Some(path) => std::fs::write(path, out + "\n").unwrap_or_else(|e| panic!("could not write {path}: {e}")),
+        None => println!("{out}")
+    }
+}
+
+fn play(source: &Source, settings: &ModeSettings, seeds: u64, eval: &(impl Eval + Sync)) -> Vec<SurvivalStats> {
+    (0..seeds).into_par_iter().map(|seed| match source {
+        Source::Pattern(pattern) => survive(&mut Scripted::new(*pattern, settings.pps), settings, eval, seed),
+        Source::Poisson { attacks_per_minute, lines } => survive(&mut Poisson::new(*attacks_per_minute, *lines, settings.pps, seed), settings, eval, seed),
+        Source::Log { lines, .. } => survive(&mut AttackLog::new(lines.clone()), settings, eval, seed)
+    }).collect()
+}
+
+// every game added together
+fn totals(games: &[SurvivalStats]) -> SurvivalStats {
+    games.iter().fold(SurvivalStats::default(), |mut t, g| {
+        t.pieces += g.pieces;
+        t.attack += g.attack;
+        t.lines += g.lines;
+        t.garbage_received += g.garbage_received;
+        t.garbage_cleared += g.garbage_cleared;
+        t.spins += g.spins;
+        t.minis += g.minis;
+        t.quads += g.quads;
+        t.pcs += g.pcs;
+        t.b2b_chains.extend(&g.b2b_chains);
+        t
+    })
+}
+
+// (chain length, how many chains were that long), shortest first
+fn histogram(chains: &[u32]) -> Vec<(u32, usize)> {
+    let mut counts = std::collections::BTreeMap::new();
+    for &c in chains {
+        *counts.entry(c).or_insert(0) += 1;
+    }
+    counts.into_iter().collect()
+}
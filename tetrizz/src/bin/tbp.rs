@@ -0,0 +1,68 @@
+use std::io::{BufRead, Write};
+
+use tetrizz::{
+    error::BotError,
+    eval::base::MinimalEval,
+    opener::Book,
+    tbp::{Bot, BotMessage, FrontendMessage}
+};
+
+// usage: tbp [--depth N] [--width N] [--book FILE] [--pc HEIGHT]
+// speaks the tetris bot protocol over stdin/stdout, one json message per line. anything that goes wrong is reported on
+// stderr, since tbp has no message for it, and a suggest that fails still gets an empty suggestion
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let arg = |name: &str, default: usize| args.iter()
+        .position(|a| a == name)
+        .map(|i| args.get(i + 1).and_then(|d| d.parse().ok()).unwrap_or_else(|| panic!("{name} needs a number")))
+        .unwrap_or(default);
+
+    let eval = MinimalEval { values: [-333.5903388433292, -257.50971325030974, -51.15233297009883, -306.7533752401169, -185.4127319664896, -107.0342999530273, -369.0213829655287, 96.63015564572557, -476.6250155906852, -58.72828521759004, 1068.1212353788042, -1016.9857268318688, -457.5993254187147, 2280.857332747434, 0.0] };
+    let mut bot = Bot::new(eval, arg("--depth", 6), arg("--width", 2000));
+    bot.book = load_book(&args);
+    bot.pc_height = arg("--pc", 4);
+
+    let mut out = std::io::stdout().lock();
+    send(&mut out, &Bot::<MinimalEval>::info());
+    for line in std::io::stdin().lock().lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let msg: FrontendMessage = match serde_json::from_str(&line) {
+            Ok(m) => m,
+            Err(e) => {
+                eprintln!("{}", BotError::from(e));
+                continue;
+            }
+        };
+        let quit = matches!(msg, FrontendMessage::Quit);
+        let fallback = msg.error_reply();
+        match bot.handle(msg) {
+            Ok(Some(reply)) => send(&mut out, &reply),
+            Ok(None) => {}
+            Err(e) => {
+                eprintln!("{e}");
+                if let Some(reply) = fallback {
+                    send(&mut out, &reply);
+                }
+            }
+        }
+        if quit {
+            break;
+        }
+    }
+}
+
+// the builtin book unless a file is given. an empty file turns the book off
+fn load_book(args: &[String]) -> Book {
+    let Some(path) = args.iter().position(|a| a == "--book").map(|i| args.get(i + 1).expect("--book needs a file")) else {
+        return Book::builtin();
+    };
+    let text = std::fs::read_to_string(path).unwrap_or_else(|e| panic!("could not read {path}: {e}"));
+    Book::parse(&text).unwrap_or_else(|e| panic!("{path}: {e}"))
+}
+
+fn send(out: &mut impl Write, msg: &BotMessage) {
+    writeln!(out, "{}", serde_json::to_string(msg).unwrap()).and_then(|_| out.flush()).expect("stdout closed");
+}
@@ -1,6 +1,6 @@
 use  utils::{
     data::{Board, Piece},
-    game::Game
+    game::{Game, GameConfig}
 };
 use tetrizz::{
     search::search,
@@ -9,14 +9,35 @@ use tetrizz::{
 
 fn main() {
     let game = Game {
-        board: Board { cols: [524287,262143,262143,131071,131071,131071,65534,21844,0,0] },
+        board: Board::from_ascii("
+            #.........
+            ###.......
+            ######....
+            #######...
+            ########..
+            #######...
+            ########..
+            #######...
+            ########..
+            #######...
+            ########..
+            #######...
+            ########..
+            #######...
+            ########..
+            #######...
+            ########..
+            #######...
+            ######....
+        ").unwrap(),
         hold: None,
         b2b: 10,
         combo: 2,
-        incoming_garbage: 0
+        incoming_garbage: 0,
+        config: GameConfig::default()
     };
     let queue = vec![Piece::I, Piece::S, Piece::J, Piece::Z, Piece::T, Piece::O, Piece::L, Piece::S, Piece::J, Piece::Z, Piece::T, Piece::L, Piece::O, Piece::I, Piece::S, Piece::T, Piece::Z];
-    let eval = MinimalEval { values: [-333.5903388433292, -257.50971325030974, -51.15233297009883, -306.7533752401169, -185.4127319664896, -107.0342999530273, -369.0213829655287, 96.63015564572557, -476.6250155906852, -58.72828521759004, 1068.12123537880416, -1016.9857268318688, -457.5993254187147, 2280.85733274743393] };
+    let eval = MinimalEval { values: [-333.5903388433292, -257.50971325030974, -51.15233297009883, -306.7533752401169, -185.4127319664896, -107.0342999530273, -369.0213829655287, 96.63015564572557, -476.6250155906852, -58.72828521759004, 1068.12123537880416, -1016.9857268318688, -457.5993254187147, 2280.85733274743393, 0.0] };
     
     let mut res: Vec<f64> = vec![];
     for depth in 1..=15 {
@@ -6,57 +6,39 @@ use rayon::prelude::*;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::io::Write;
 
-use utils::{
-    data::Piece,
-    game::Game,
-    queue::extend_queue
-};
+use utils::queue::SeededBag;
 use tetrizz::{
     eval::base::MinimalEval,
-    battle::{Battle, Player}
+    battle::{Battle, Player},
+    garbage::{Pattern, Scripted}
 };
 
 const GAMES_PLAYED: usize = 5;
 const MAX_MOVES: usize = 1000;
+const PPS: f32 = 2.0;
 
+// both agents play the same queue against the same garbage, which picks up after 200 pieces like the `multi` factor
+// did to battle garbage. whoever lasts longer wins, or whoever sent more if both make it to MAX_MOVES
 pub fn win_loss(agent: &Agent, opponent: &Agent) -> i8 {
-    let mut queue0: Vec<Piece> = vec![];
-    let mut queue1: Vec<Piece> = vec![];
-    extend_queue(&mut queue0, 5);
-    extend_queue(&mut queue1, 5);
-    let mut battle = Battle {
-        player0: Player { game: Game::new_empty(), queue: vec![], eval: agent.eval.clone() },
-        player1: Player { game: Game::new_empty(), queue: vec![], eval: opponent.eval.clone() },
-        who: 0
-    };
-
-    let mut max_b2b = 0;
-    let mut game_ended = false;
-    
-    for _ in 0..MAX_MOVES {
-        if queue0.len() <= 14 { extend_queue(&mut queue0, 1); }
-        if queue1.len() <= 14 { extend_queue(&mut queue1, 1); }
-        battle.player0.queue = queue0[..7].iter().copied().collect();
-        battle.player1.queue = queue1[..7].iter().copied().collect();
-        let who = battle.who;
-        let result = battle.advance();
-        if result.is_none() {
-            game_ended = true;
-            break;
-        }
-
-        if battle.player0.game.b2b > max_b2b {
-            max_b2b = battle.player0.game.b2b;
+    let seed = rand::rng().random();
+    let play = |eval: &MinimalEval| {
+        let source = Scripted::new(Pattern::Increasing { apm: 30.0, growth: 0.01, after: 200 }, PPS);
+        let mut battle = Battle::new(Player::new(eval.clone()), source);
+        let mut bag = SeededBag::new(seed);
+        let mut attack = 0;
+        for moves in 0..MAX_MOVES {
+            if battle.player.queue.len() <= 7 {
+                bag.extend(&mut battle.player.queue, 1);
+            }
+            match battle.advance() {
+                // garbage can push the stack out the top too, which search doesn't see coming
+                Ok((_, info)) if battle.player.game.board.max_height() <= 40 => attack += info.outgoing_attack as u32,
+                _ => return (moves, attack)
+            }
         }
-
-        if who == 0 { queue0.remove(0); } else { queue1.remove(0); }
-
-        // let multi = 1.0 + 0.001 * i.saturating_sub(200) as f64;
-        // let garb = if who == 0 { &mut battle.player1.game.incoming_garbage } else { &mut battle.player0.game.incoming_garbage };
-        // *garb = (*garb as f64 * multi) as u16;
-    }
-
-    if !game_ended { 0 } else if battle.who == 0 { 1 } else { -1 }
+        (MAX_MOVES, attack)
+    };
+    play(&agent.eval).cmp(&play(&opponent.eval)) as i8
 }
 
 // pub fn win_loss(agent: &Agent, opponent: &Agent) -> i8 {
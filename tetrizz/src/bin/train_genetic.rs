@@ -6,18 +6,14 @@ use rayon::prelude::*;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::io::Write;
 
-use utils::{
-    data::{Piece, PieceLocation},
-    game::{Game, PlacementInfo},
-    queue::extend_queue
-};
+use utils::queue::extend_queue;
 use tetrizz::{
     search::search,
     eval::{
         base::Eval,
         feature0::{FeatureNonLinearEval, FLAT_SIZE}
     },
-    battle::{Battle, Player}
+    battle::{Battle, Player, Side}
 };
 
 const MAX_MOVES: usize = 500;
@@ -28,38 +24,27 @@ pub fn eval_fitness(agent: &Agent, opponent: &Agent) -> f64 {
     let mut total_max_b2b = 0.0;
 
     for _ in 0..GAMES_PLAYED {
-        let mut queue0: Vec<Piece> = vec![];
-        let mut queue1: Vec<Piece> = vec![];
-        extend_queue(&mut queue0, 5);
-        extend_queue(&mut queue1, 5);
-        let mut battle = Battle {
-            player0: Player { game: Game::new_empty(), queue: vec![], eval: FeatureNonLinearEval::from_array(&agent.weights) },
-            player1: Player { game: Game::new_empty(), queue: vec![], eval: FeatureNonLinearEval::from_array(&opponent.weights) },
-            who: 0
-        };
+        let mut battle = Battle::new(
+            Player::new(FeatureNonLinearEval::from_array(&agent.weights)),
+            Player::new(FeatureNonLinearEval::from_array(&opponent.weights))
+        );
 
         let mut max_b2b = 0;
-        let mut game_ended = false;
+        let mut lost = None;
         
         for _ in 0..MAX_MOVES {
-            if queue0.len() <= 14 { extend_queue(&mut queue0, 1); }
-            if queue1.len() <= 14 { extend_queue(&mut queue1, 1); }
-            battle.player0.queue = queue0[..7].iter().copied().collect();
-            battle.player1.queue = queue1[..7].iter().copied().collect();
-            let who = battle.who;
-            let result = battle.advance();
-            if result.is_none() {
-                game_ended = true;
+            if battle.player.queue.len() <= 7 { extend_queue(&mut battle.player.queue, 1); }
+            if battle.opponent.queue.len() <= 7 { extend_queue(&mut battle.opponent.queue, 1); }
+            if let Err(side) = battle.advance() {
+                lost = Some(side);
                 break;
             }
 
-            if battle.player0.game.b2b > max_b2b {
-                max_b2b = battle.player0.game.b2b;
+            if battle.player.game.b2b > max_b2b {
+                max_b2b = battle.player.game.b2b;
             }
-
-            if who == 0 { queue0.remove(0); } else { queue1.remove(0); }
         }
-        fitness += if game_ended { 0.5 } else if battle.who == 0 { 1.0 } else { -0.0 };
+        fitness += match lost { Some(Side::Opponent) => 1.0, Some(Side::Player) => 0.0, None => 0.5 };
         total_max_b2b += (max_b2b as f64).ln_1p();
     }
     fitness * total_max_b2b / GAMES_PLAYED as f64
@@ -0,0 +1,91 @@
+use utils::{
+    cheese::Cheese,
+    game::Game,
+    queue::SeededBag
+};
+use crate::{
+    eval::base::Eval,
+    search::search
+};
+
+// cheese race: dig through a fixed number of cheese lines, with only some of them on the board at a time. new lines come
+// in from the bottom as soon as old ones are cleared, until the race runs out
+#[derive(Debug, Clone)]
+pub struct CheeseRace {
+    pub lines: usize,
+    // garbage lines kept on the board while there are any left to add
+    pub visible: usize,
+    pub messiness: f64,
+    pub depth: usize,
+    pub width: usize,
+    // the run is counted as unfinished after this many pieces
+    pub max_pieces: usize
+}
+
+impl Default for CheeseRace {
+    fn default() -> Self {
+        Self { lines: 100, visible: 10, messiness: 1.0, depth: 6, width: 2000, max_pieces: 1000 }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CheeseResult {
+    pub seed: u64,
+    pub pieces: usize,
+    // garbage lines cleared, not counting lines made of only placed pieces
+    pub cleared: usize,
+    pub finished: bool
+}
+
+impl CheeseResult {
+    pub fn pieces_per_line(&self) -> f64 {
+        self.pieces as f64 / self.cleared.max(1) as f64
+    }
+}
+
+// the seed picks both the queue and the cheese
+pub fn run(race: &CheeseRace, eval: &impl Eval, seed: u64) -> CheeseResult {
+    let mut bag = SeededBag::new(seed);
+    let mut cheese = Cheese::new(seed, race.messiness);
+    let mut game = Game::new_empty();
+    let mut queue = vec![];
+
+    let mut garbage = race.visible.min(race.lines);
+    let mut left = race.lines - garbage;
+    cheese.add_lines(&mut game.board, garbage);
+
+    let mut result = CheeseResult { seed, pieces: 0, cleared: 0, finished: false };
+    while result.pieces < race.max_pieces {
+        while queue.len() < race.depth + 2 {
+            bag.extend(&mut queue, 1);
+        }
+        let Some(loc) = search(&game, &queue[..race.depth + 1], eval, race.depth, race.width) else {
+            break;
+        };
+
+        // garbage rows this placement fills, before they are cleared away
+        let mut board = game.board;
+        board.put_piece(&loc);
+        let full = (0..10).fold(u64::MAX, |acc, x| acc & board.cols[x]);
+        let cleared = (full & 1u64.checked_shl(garbage as u32).map_or(u64::MAX, |b| b - 1)).count_ones() as usize;
+
+        let current = queue.remove(0);
+        if loc.piece != current && game.hold.is_none() {
+            queue.remove(0);
+        }
+        game.advance(current, &loc);
+        result.pieces += 1;
+        result.cleared += cleared;
+        garbage -= cleared;
+
+        let added = (race.visible - garbage).min(left);
+        cheese.add_lines(&mut game.board, added);
+        garbage += added;
+        left -= added;
+        if garbage == 0 {
+            result.finished = true;
+            break;
+        }
+    }
+    result
+}
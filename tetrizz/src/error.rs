@@ -0,0 +1,79 @@
+use utils::{
+    data::{PieceError, PieceLocation},
+    input::KeygenError
+};
+
+// everything a bot frontend can send that the bot can't act on. none of these should kill the process: they are sent back
+// (or logged) and the next message is handled as usual
+
+#[derive(Debug)]
+pub enum BotError {
+    Json(serde_json::Error),
+    Piece(PieceError),
+    Board(String),
+    EmptyQueue,
+    // a message that needs a game before one was started
+    NoGame,
+    // the piece doesn't match the queue and hold, or doesn't fit on the board
+    IllegalMove(PieceLocation),
+    // the search found nothing that doesn't top out
+    NoMove,
+    Keygen(KeygenError)
+}
+
+impl BotError {
+    // short name for the error replies, so clients can match on it without parsing messages
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            BotError::Json(_) => "invalid_json",
+            BotError::Piece(_) => "invalid_piece",
+            BotError::Board(_) => "invalid_board",
+            BotError::EmptyQueue => "empty_queue",
+            BotError::NoGame => "no_game",
+            BotError::IllegalMove(_) => "illegal_move",
+            BotError::NoMove => "no_move",
+            BotError::Keygen(KeygenError::Blocked(_)) => "blocked",
+            BotError::Keygen(KeygenError::Unreachable(_)) => "unreachable_location"
+        }
+    }
+
+    // {"error": {"kind": ..., "message": ...}}
+    pub fn reply(&self) -> serde_json::Value {
+        serde_json::json!({ "error": { "kind": self.kind(), "message": self.to_string() } })
+    }
+}
+
+impl std::fmt::Display for BotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BotError::Json(e) => write!(f, "invalid message: {e}"),
+            BotError::Piece(e) => write!(f, "{e}"),
+            BotError::Board(s) => write!(f, "invalid board: {s}"),
+            BotError::EmptyQueue => write!(f, "the queue is empty"),
+            BotError::NoGame => write!(f, "no game has been started"),
+            BotError::IllegalMove(loc) => write!(f, "illegal move {loc:?}"),
+            BotError::NoMove => write!(f, "no move found"),
+            BotError::Keygen(e) => write!(f, "{e}")
+        }
+    }
+}
+
+impl std::error::Error for BotError {}
+
+impl From<serde_json::Error> for BotError {
+    fn from(e: serde_json::Error) -> Self {
+        BotError::Json(e)
+    }
+}
+
+impl From<PieceError> for BotError {
+    fn from(e: PieceError) -> Self {
+        BotError::Piece(e)
+    }
+}
+
+impl From<KeygenError> for BotError {
+    fn from(e: KeygenError) -> Self {
+        BotError::Keygen(e)
+    }
+}
@@ -1,8 +1,13 @@
-use utils::{game::{Game, PlacementInfo}, data::Spin};
+use utils::{game::{GameState, PlacementInfo}, data::Spin};
 use rand::Rng;
 
 pub trait Eval {
-    fn value(&self, game: &Game, info: &PlacementInfo) -> f64;
+    fn value(&self, game: &GameState, info: &PlacementInfo) -> f64;
+
+    // placements the search looks at. by default only spin singles clear lines, so attack evals build for b2b
+    fn allows(&self, info: &PlacementInfo) -> bool {
+        !(info.lines_cleared > 1 || (info.lines_cleared == 1 && info.spin == Spin::None))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -25,7 +30,7 @@ impl MinimalEval {
 }
 
 impl Eval for MinimalEval {
-    fn value(&self, game: &Game, info: &PlacementInfo) -> f64 {
+    fn value(&self, game: &GameState, info: &PlacementInfo) -> f64 {
         // height
         let heights: [i32; 10] = game.board.cols.map(|c| 64 - c.leading_zeros() as i32);
 
@@ -81,7 +86,7 @@ impl Eval for MinimalEval {
                 continue;
             }
 
-            let a = heights.get(x - 1).copied().unwrap_or(99);
+            let a = heights.get(x.wrapping_sub(1)).copied().unwrap_or(99);
             let b = heights[x];
             let c = heights.get(x + 1).copied().unwrap_or(99);
 
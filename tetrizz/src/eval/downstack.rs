@@ -0,0 +1,60 @@
+use utils::game::{GameState, PlacementInfo};
+use crate::eval::base::Eval;
+
+// for digging through garbage as fast as possible rather than sending attack. any line clear is allowed, and the board
+// is judged by how much is left in the way of the holes
+#[derive(Debug, Clone)]
+pub struct DownstackEval {
+    pub holes: f64,
+    // filled cells above each hole
+    pub coveredness: f64,
+    pub max_height: f64,
+    pub bumpiness: f64,
+    pub row_transitions: f64,
+    pub lines_cleared: f64
+}
+
+impl Default for DownstackEval {
+    fn default() -> Self {
+        Self { holes: -4.0, coveredness: -1.0, max_height: -0.5, bumpiness: -0.3, row_transitions: -0.2, lines_cleared: 1.0 }
+    }
+}
+
+impl Eval for DownstackEval {
+    fn value(&self, game: &GameState, info: &PlacementInfo) -> f64 {
+        let heights: [i32; 10] = game.board.cols.map(|c| 64 - c.leading_zeros() as i32);
+        let max_height = *heights.iter().max().unwrap();
+
+        let mut holes = 0;
+        let mut coveredness = 0;
+        for &c in &game.board.cols {
+            let h = 64 - c.leading_zeros();
+            // a full column is 64 high, too far to shift
+            let under = 1u64.checked_shl(h).map_or(u64::MAX, |b| b - 1);
+            let mut empty = !c & under;
+            holes += empty.count_ones();
+            while empty != 0 {
+                let y = empty.trailing_zeros();
+                coveredness += (c >> y).count_ones();
+                empty &= empty - 1;
+            }
+        }
+
+        let bumpiness = heights.windows(2).map(|h| (h[0] - h[1]).abs()).sum::<i32>();
+        let row_transitions = game.board.cols
+            .windows(2)
+            .map(|c| (c[0] ^ c[1]).count_ones())
+            .sum::<u32>();
+
+        self.holes * holes as f64
+        + self.coveredness * coveredness as f64
+        + self.max_height * max_height as f64
+        + self.bumpiness * bumpiness as f64
+        + self.row_transitions * row_transitions as f64
+        + self.lines_cleared * info.lines_cleared as f64
+    }
+
+    fn allows(&self, _info: &PlacementInfo) -> bool {
+        true
+    }
+}
@@ -1,4 +1,4 @@
-use utils::{game::{Game, PlacementInfo}, data::Spin};
+use utils::{game::{GameState, PlacementInfo}, data::Spin};
 use crate::eval::base::Eval;
 use rand::Rng;
 
@@ -31,7 +31,7 @@ impl FeatureNonLinearEval {
 
 impl Eval for FeatureNonLinearEval {
     #[inline]
-    fn value(&self, game: &Game, info: &PlacementInfo) -> f64 {
+    fn value(&self, game: &GameState, info: &PlacementInfo) -> f64 {
         // height
         let heights: [i32; 10] = game.board.cols.map(|c| 64 - c.leading_zeros() as i32);
 
@@ -75,7 +75,7 @@ impl Eval for FeatureNonLinearEval {
         
         for x in 0..10 {
             let b = heights[x];
-            let a = heights.get(x - 1).copied().unwrap_or(b + 3);
+            let a = heights.get(x.wrapping_sub(1)).copied().unwrap_or(b + 3);
             let c = heights.get(x + 1).copied().unwrap_or(b + 3);
 
             dependencies += (a - 1 > b && c - 1 > b) as i32;
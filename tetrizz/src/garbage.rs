@@ -0,0 +1,135 @@
+use serde::Serialize;
+use utils::{
+    queue::TetrioRng,
+    replay::{PlayerReplay, ReplayEvent}
+};
+use crate::battle::GarbageSource;
+
+// scripted opponents. time is counted in pieces at a fixed pps, so the same source sends the same garbage every run
+
+// how garbage comes in. time comes from the piece count at the given pps
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Pattern {
+    // evenly spread, this many lines a minute
+    Constant { apm: f32 },
+    // this many lines at once, every so many seconds
+    Burst { lines: u16, every: f32 },
+    // like the `multi` factor commented out in train.rs: constant, until after `after` pieces the rate goes up by `growth`
+    // of the starting rate with every piece
+    Increasing { apm: f32, growth: f32, after: usize }
+}
+
+impl Pattern {
+    // lines sent while piece number `piece` (from 0) is being placed. fractions add up over pieces
+    pub fn lines(&self, piece: usize, pps: f32) -> f32 {
+        let dt = 1.0 / pps;
+        match *self {
+            Pattern::Constant { apm } => apm / 60.0 * dt,
+            Pattern::Burst { lines, every } => {
+                let (start, end) = (piece as f32 * dt, (piece + 1) as f32 * dt);
+                ((end / every).floor() - (start / every).floor()) * lines as f32
+            }
+            Pattern::Increasing { apm, growth, after } => apm / 60.0 * dt * (1.0 + growth * piece.saturating_sub(after) as f32)
+        }
+    }
+}
+
+// a pattern played out piece by piece, sending whole lines as soon as they add up
+#[derive(Debug, Clone)]
+pub struct Scripted {
+    pub pattern: Pattern,
+    pub pps: f32,
+    piece: usize,
+    pending: f32
+}
+
+impl Scripted {
+    pub fn new(pattern: Pattern, pps: f32) -> Self {
+        Self { pattern, pps, piece: 0, pending: 0.0 }
+    }
+}
+
+impl GarbageSource for Scripted {
+    fn next(&mut self, _attack: u16) -> u16 {
+        self.pending += self.pattern.lines(self.piece, self.pps);
+        self.piece += 1;
+        let lines = self.pending as u16;
+        self.pending -= lines as f32;
+        lines
+    }
+}
+
+// attacks of a fixed size arriving at random, a given number of them a minute on average
+#[derive(Debug, Clone)]
+pub struct Poisson {
+    pub attacks_per_minute: f32,
+    pub lines: u16,
+    pub pps: f32,
+    rng: TetrioRng
+}
+
+impl Poisson {
+    pub fn new(attacks_per_minute: f32, lines: u16, pps: f32, seed: u64) -> Self {
+        Self { attacks_per_minute, lines, pps, rng: TetrioRng::new(seed) }
+    }
+}
+
+impl GarbageSource for Poisson {
+    // knuth's method, the rate per piece is small
+    fn next(&mut self, _attack: u16) -> u16 {
+        let limit = (-(self.attacks_per_minute / 60.0 / self.pps) as f64).exp();
+        let mut attacks = 0;
+        let mut p = self.rng.next_float();
+        while p > limit {
+            attacks += 1;
+            p *= self.rng.next_float();
+        }
+        attacks * self.lines
+    }
+}
+
+// garbage taken from a log of an opponent's attacks, sent at the piece it would have arrived on. once the log runs out
+// nothing more comes
+#[derive(Debug, Clone)]
+pub struct AttackLog {
+    // lines per piece
+    pub lines: Vec<u16>,
+    piece: usize
+}
+
+impl AttackLog {
+    pub fn new(lines: Vec<u16>) -> Self {
+        Self { lines, piece: 0 }
+    }
+
+    // (seconds, lines) attacks, bucketed into the pieces of a player at pps
+    pub fn from_timed(attacks: &[(f32, u16)], pps: f32) -> Self {
+        let mut lines = vec![];
+        for &(seconds, amount) in attacks {
+            let piece = (seconds * pps) as usize;
+            if lines.len() <= piece {
+                lines.resize(piece + 1, 0);
+            }
+            lines[piece] += amount;
+        }
+        Self::new(lines)
+    }
+
+    // the garbage the player in a tetr.io replay received, which is everything their opponent sent
+    pub fn from_replay(replay: &PlayerReplay, pps: f32) -> Self {
+        let attacks: Vec<(f32, u16)> = replay.events.iter().filter_map(|e| match e.event {
+            ReplayEvent::Garbage { amount, .. } => Some(((e.frame as f64 + e.subframe) as f32 / 60.0, amount)),
+            _ => None
+        }).collect();
+        Self::from_timed(&attacks, pps)
+    }
+}
+
+impl GarbageSource for AttackLog {
+    fn next(&mut self, _attack: u16) -> u16 {
+        let lines = self.lines.get(self.piece).copied().unwrap_or(0);
+        self.piece += 1;
+        lines
+    }
+}
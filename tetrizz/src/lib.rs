@@ -2,5 +2,15 @@ pub mod search;
 pub mod eval {
     pub mod base;
     pub mod feature0;
+    pub mod downstack;
 }
-pub mod battle;
\ No newline at end of file
+pub mod battle;
+pub mod tbp;
+pub mod server;
+pub mod error;
+pub mod opener;
+pub mod pc;
+pub mod cheese;
+pub mod modes;
+pub mod survival;
+pub mod garbage;
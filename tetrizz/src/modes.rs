@@ -0,0 +1,189 @@
+use utils::{
+    cheese::Cheese,
+    data::Spin,
+    game::{Game, GameState, PlacementInfo},
+    input::{Finesse, keygen},
+    queue::SeededBag
+};
+use crate::{
+    battle::GarbageSource,
+    eval::{base::Eval, downstack::DownstackEval},
+    garbage::{Pattern, Scripted},
+    search::search
+};
+
+// single player modes. time isn't simulated, the bot is assumed to play at a steady pps, so a piece takes 1 / pps seconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    // clear this many lines in as few pieces as possible
+    Sprint { lines: u32 },
+    // as many points as possible before the time runs out
+    Blitz { seconds: f32 },
+    // no goal and no garbage, just keep playing
+    Zen,
+    // garbage comes in at this many lines per minute until the bot tops out. survival::survive plays against any
+    // garbage source and keeps more stats, this is the constant one with the same results as the other modes
+    Survival { apm: f32 }
+}
+
+impl Mode {
+    pub const SPRINT: Self = Mode::Sprint { lines: 40 };
+    pub const BLITZ: Self = Mode::Blitz { seconds: 120.0 };
+
+    // what search should go for in this mode
+    pub fn eval(&self) -> ModeEval {
+        let mut eval = ModeEval { board: DownstackEval::default(), points: 0.0, spins: 0.0 };
+        match self {
+            Mode::Blitz { .. } => eval.points = 0.01,
+            // fewer pieces and keys to the goal. clearing lines as soon as possible ends up taking more pieces (about 107
+            // against 103 for 40 lines at depth 3), and spins cost a softdrop and rotations for nothing
+            Mode::Sprint { .. } => {
+                eval.board.lines_cleared = 0.0;
+                eval.spins = -2.0;
+            }
+            Mode::Zen | Mode::Survival { .. } => {}
+        }
+        eval
+    }
+}
+
+// blitz points for a placement (tetr.io uses guideline scoring), with game being the state after it. b2b clears are worth
+// half again, and combos and perfect clears are added on top. everything is multiplied by the level
+pub fn points(game: &GameState, info: &PlacementInfo, level: u32) -> u32 {
+    let lines = info.lines_cleared as usize;
+    let mut base = match info.spin {
+        Spin::Full => [400, 800, 1200, 1600, 1600][lines],
+        Spin::Mini => [100, 200, 400, 400, 400][lines],
+        Spin::None => [0, 100, 300, 500, 800][lines]
+    };
+    if lines > 0 && info.b2b_clear && game.b2b > 0 {
+        base += base / 2;
+    }
+    let combo = if lines > 0 { 50 * game.combo.max(0) as u32 } else { 0 };
+    let pc = if info.pc { 3500 } else { 0 };
+    (base + combo + pc) * level
+}
+
+// the downstack eval's board shape (so every kind of line clear is fine), plus the points a placement scores in blitz
+#[derive(Debug, Clone)]
+pub struct ModeEval {
+    pub board: DownstackEval,
+    pub points: f64,
+    // per spin placement, which takes a softdrop and extra rotations
+    pub spins: f64
+}
+
+impl Eval for ModeEval {
+    fn value(&self, game: &GameState, info: &PlacementInfo) -> f64 {
+        let spin = (info.spin != Spin::None) as u8 as f64;
+        self.board.value(game, info) + self.points * points(game, info, 1) as f64 + self.spins * spin
+    }
+
+    fn allows(&self, _info: &PlacementInfo) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ModeSettings {
+    pub depth: usize,
+    pub width: usize,
+    pub pps: f32,
+    // what the keys are picked for. anything above the fewest keypresses for a placement is a finesse fault
+    pub finesse: Finesse,
+    // a run with no end (zen, or survival that never tops out) stops here
+    pub max_pieces: usize
+}
+
+impl Default for ModeSettings {
+    fn default() -> Self {
+        Self { depth: 6, width: 2000, pps: 2.0, finesse: Finesse::Keys, max_pieces: 1000 }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ModeResult {
+    pub seed: u64,
+    pub pieces: usize,
+    pub lines: u32,
+    pub points: u32,
+    pub attack: u32,
+    // holds included
+    pub keys: usize,
+    // pieces placed with more keys than they needed
+    pub finesse_faults: usize,
+    pub topped_out: bool
+}
+
+impl ModeResult {
+    // seconds, at the pps the run was played at
+    pub fn time(&self, pps: f32) -> f32 {
+        self.pieces as f32 / pps
+    }
+
+    pub fn keys_per_piece(&self) -> f32 {
+        self.keys as f32 / self.pieces.max(1) as f32
+    }
+}
+
+// plays one game of the mode. the seed picks the queue and where garbage holes go
+pub fn play(mode: &Mode, settings: &ModeSettings, eval: &impl Eval, seed: u64) -> ModeResult {
+    let mut bag = SeededBag::new(seed);
+    let mut holes = Cheese::new(seed, 1.0);
+    let mut game = Game::new_empty();
+    let mut queue = vec![];
+    let mut result = ModeResult { seed, ..Default::default() };
+    let mut source = match *mode {
+        Mode::Survival { apm } => Some(Scripted::new(Pattern::Constant { apm }, settings.pps)),
+        _ => None
+    };
+
+    loop {
+        let done = match *mode {
+            Mode::Sprint { lines } => result.lines >= lines,
+            Mode::Blitz { seconds } => result.time(settings.pps) >= seconds,
+            Mode::Zen | Mode::Survival { .. } => false
+        };
+        if done || result.pieces >= settings.max_pieces {
+            break;
+        }
+        while queue.len() < settings.depth + 2 {
+            bag.extend(&mut queue, 1);
+        }
+        let Some(loc) = search(&game, &queue[..settings.depth + 1], eval, settings.depth, settings.width) else {
+            result.topped_out = true;
+            break;
+        };
+
+        let Ok(keys) = keygen(&game.board, &loc, &settings.finesse, &game.config) else {
+            result.topped_out = true;
+            break;
+        };
+        let fewest = keygen(&game.board, &loc, &Finesse::Keys, &game.config).map_or(keys.len(), |k| k.len());
+        result.keys += keys.len();
+        result.finesse_faults += (keys.len() > fewest) as usize;
+
+        let current = queue.remove(0);
+        if loc.piece != current {
+            result.keys += 1;
+            if game.hold.is_none() {
+                queue.remove(0);
+            }
+        }
+        let info = game.advance_with(current, &loc, || holes.next_hole());
+        if let Some(source) = &mut source {
+            game.incoming_garbage = game.incoming_garbage.saturating_add(source.next(info.outgoing_attack));
+        }
+        let level = 1 + result.lines / 10;
+        result.pieces += 1;
+        result.lines += info.lines_cleared as u32;
+        result.attack += info.outgoing_attack as u32;
+        result.points += points(&game.state(), &info, level);
+        // topping out on garbage, which search doesn't see coming
+        if game.board.max_height() > 40 {
+            result.topped_out = true;
+            break;
+        }
+    }
+    result
+}
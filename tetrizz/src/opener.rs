@@ -0,0 +1,292 @@
+use utils::{
+    data::{Board, Piece, PieceLocation, Spin, ROT},
+    game::{Game, GameConfig},
+    movegen::movegen_piece
+};
+
+// opener books. an opener is a shape drawn like utils::ascii boards, with each placement written in its piece's letter:
+//
+//     name: tsd
+//     before: TS
+//     ...S......
+//     L..SSOOZZ.
+//     LtttSOOJZZ
+//     LLtIIIIJJJ
+//
+// - `#` (or X, G) cells must already be on the board, so later stages of an opener can be separate entries
+// - a lowercase letter is a placement that has to be a spin. spins are placed after everything else
+// - `before: AB` (repeatable) only follows the opener if A comes before B in hold + queue, when both are known
+// - a placement that clears lines has to be the last one
+//
+// placements can happen in any order the queue and hold allow, as long as movegen can reach each one when it is
+// placed. every opener is also tried mirrored. entries in a book file are separated by `---` lines.
+//
+// openers longer than a bag are one entry per stage, with `#` for what the earlier stages left. the builtin book
+// (openers.txt) has the tsd above, then tki, pco, mko and dt cannon each with a second bag: the tki, mko and dt cannon
+// spins (a tsd then a tst for dt cannon) and the pco perfect clear. --book loads another file instead
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpenerError {
+    Width { line: usize, width: usize },
+    Cell { line: usize, cell: char },
+    // cells of one letter that don't split into pieces
+    Shape(char),
+    Header(String)
+}
+
+impl std::fmt::Display for OpenerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OpenerError::Width { line, width } => write!(f, "line {line} has {width} cells, expected 10"),
+            OpenerError::Cell { line, cell } => write!(f, "line {line} has an invalid cell {cell:?}"),
+            OpenerError::Shape(c) => write!(f, "the {c:?} cells don't make up whole pieces"),
+            OpenerError::Header(h) => write!(f, "invalid header {h:?}")
+        }
+    }
+}
+
+impl std::error::Error for OpenerError {}
+
+#[derive(Debug, Clone)]
+pub struct Placement {
+    pub loc: PieceLocation,
+    pub spin: bool
+}
+
+#[derive(Debug, Clone)]
+pub struct Opener {
+    pub name: String,
+    // cells that have to be there before the opener starts
+    pub base: Board,
+    pub placements: Vec<Placement>,
+    pub before: Vec<(Piece, Piece)>
+}
+
+impl Opener {
+    pub fn parse(s: &str) -> Result<Self, OpenerError> {
+        let mut name = String::new();
+        let mut before = vec![];
+        let mut rows = vec![];
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some((key, value)) = line.split_once(':') {
+                let header_err = || OpenerError::Header(line.to_string());
+                let value = value.trim();
+                match key.trim() {
+                    "name" => name = value.to_string(),
+                    "before" => {
+                        let pieces: Vec<Piece> = value.chars().map(Piece::from_letter).collect::<Option<_>>().ok_or_else(header_err)?;
+                        let [a, b] = pieces[..] else { return Err(header_err()) };
+                        before.push((a, b));
+                    }
+                    _ => return Err(header_err())
+                }
+                continue;
+            }
+            let cells: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+            if cells.len() != 10 {
+                return Err(OpenerError::Width { line: i + 1, width: cells.len() });
+            }
+            if let Some(&cell) = cells.iter().find(|&&c| !matches!(c, '.' | '_' | '#' | 'X' | 'G') && Piece::from_letter(c).is_none()) {
+                return Err(OpenerError::Cell { line: i + 1, cell });
+            }
+            rows.push(cells);
+        }
+
+        // bottom row first
+        let grid: Vec<Vec<char>> = rows.into_iter().rev().collect();
+        let mut base = Board { cols: [0; 10] };
+        let mut placements = vec![];
+        let mut seen = vec![[false; 10]; grid.len()];
+        for y in 0..grid.len() {
+            for x in 0..10 {
+                let c = grid[y][x];
+                if matches!(c, '#' | 'X' | 'G') {
+                    base.cols[x] |= 1 << y;
+                }
+                if seen[y][x] || Piece::from_letter(c).is_none() {
+                    continue;
+                }
+                // every group of touching cells with the same letter is one piece, so two of a kind can't touch
+                let mut group = vec![(x as i8, y as i8)];
+                seen[y][x] = true;
+                let mut i = 0;
+                while i < group.len() {
+                    let (gx, gy) = group[i];
+                    for (nx, ny) in [(gx - 1, gy), (gx + 1, gy), (gx, gy - 1), (gx, gy + 1)] {
+                        if (0..10).contains(&nx) && (0..grid.len() as i8).contains(&ny)
+                            && !seen[ny as usize][nx as usize] && grid[ny as usize][nx as usize] == c {
+                            seen[ny as usize][nx as usize] = true;
+                            group.push((nx, ny));
+                        }
+                    }
+                    i += 1;
+                }
+                let piece = Piece::from_letter(c).unwrap();
+                let loc = locate(piece, &group).ok_or(OpenerError::Shape(c))?;
+                placements.push(Placement { loc, spin: c.is_ascii_lowercase() });
+            }
+        }
+        Ok(Opener { name, base, placements, before })
+    }
+
+    pub fn mirrored(&self) -> Self {
+        let mut base = self.base;
+        base.cols.reverse();
+        let placements = self.placements.iter().map(|p| {
+            let piece = mirror(p.loc.piece);
+            let cells: Vec<(i8, i8)> = p.loc.blocks().iter().map(|&(x, y)| (9 - x, y)).collect();
+            Placement { loc: locate(piece, &cells).unwrap(), spin: p.spin }
+        }).collect();
+        let before = self.before.iter().map(|&(a, b)| (mirror(a), mirror(b))).collect();
+        Opener { name: format!("{} (mirror)", self.name), base, placements, before }
+    }
+
+    // the order to make the remaining placements in with this hold and queue, as movegen finds them (with spins labelled).
+    // None if the board isn't part of the way through this opener, or the pieces can't finish it. placements past the
+    // end of the queue aren't known yet, so the plan stops there
+    pub fn plan(&self, game: &Game, queue: &[Piece]) -> Option<Vec<PieceLocation>> {
+        let mut expected = self.base;
+        let mut remaining = vec![];
+        for (i, p) in self.placements.iter().enumerate() {
+            if p.loc.blocks().iter().all(|&(x, y)| game.board.cols[x as usize] & (1 << y) > 0) {
+                expected.put_piece(&p.loc);
+            } else {
+                remaining.push(i);
+            }
+        }
+        if expected.cols != game.board.cols || remaining.is_empty() {
+            return None;
+        }
+
+        let order = |piece: Piece| game.hold.iter().chain(queue).position(|&p| p == piece);
+        for &(a, b) in &self.before {
+            let unplaced = |piece: Piece| remaining.iter().any(|&i| self.placements[i].loc.piece == piece);
+            if unplaced(a) && unplaced(b) && let (Some(i), Some(j)) = (order(a), order(b)) && i > j {
+                return None;
+            }
+        }
+        self.follow(&game.board, game.hold, queue, &remaining, &game.config)
+    }
+
+    fn follow(&self, board: &Board, hold: Option<Piece>, queue: &[Piece], remaining: &[usize], config: &GameConfig) -> Option<Vec<PieceLocation>> {
+        if remaining.is_empty() || queue.is_empty() {
+            return Some(vec![]);
+        }
+        // (piece to place, queue after it, hold after it)
+        let mut options = vec![(queue[0], &queue[1..], hold)];
+        match hold {
+            Some(h) if h != queue[0] => options.push((h, &queue[1..], Some(queue[0]))),
+            None if queue.len() > 1 => options.push((queue[1], &queue[2..], Some(queue[0]))),
+            _ => {}
+        }
+
+        let mut arena = vec![];
+        for (piece, rest, hold) in options {
+            arena.clear();
+            let mut generated = false;
+            let spin_turn = remaining.iter().all(|&i| self.placements[i].spin);
+            for (n, &i) in remaining.iter().enumerate() {
+                let target = &self.placements[i];
+                if target.loc.piece != piece || target.spin != spin_turn {
+                    continue;
+                }
+                if !generated {
+                    movegen_piece(&mut arena, board, piece, true, config);
+                    generated = true;
+                }
+                let cells = sorted(target.loc.blocks());
+                let Some(found) = arena.iter().find(|l| sorted(l.blocks()) == cells && (l.spin != Spin::None) == target.spin) else {
+                    continue;
+                };
+                let mut next = *board;
+                next.put_piece(found);
+                let full = (0..10).fold(u64::MAX, |acc, x| acc & next.cols[x]);
+                if full != 0 && remaining.len() > 1 {
+                    continue;
+                }
+                let rest_remaining: Vec<usize> = remaining.iter().enumerate().filter(|&(m, _)| m != n).map(|(_, &i)| i).collect();
+                if let Some(mut plan) = self.follow(&next, hold, rest, &rest_remaining, config) {
+                    plan.insert(0, found.clone());
+                    return Some(plan);
+                }
+            }
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Book {
+    pub openers: Vec<Opener>
+}
+
+impl Book {
+    // entries separated by `---` lines, each followed by its mirror
+    pub fn parse(s: &str) -> Result<Self, OpenerError> {
+        let mut openers = vec![];
+        let mut line = 0;
+        for entry in s.split("\n---") {
+            let opener = Opener::parse(entry).map_err(|e| match e {
+                OpenerError::Width { line: l, width } => OpenerError::Width { line: l + line, width },
+                OpenerError::Cell { line: l, cell } => OpenerError::Cell { line: l + line, cell },
+                e => e
+            })?;
+            line += entry.lines().count();
+            if !opener.placements.is_empty() {
+                let mirrored = opener.mirrored();
+                openers.push(opener);
+                openers.push(mirrored);
+            }
+        }
+        Ok(Book { openers })
+    }
+
+    // see the top of this file for what's in it
+    pub fn builtin() -> Self {
+        Self::parse(include_str!("../openers.txt")).expect("builtin opener book is broken")
+    }
+
+    // the next placement of the first opener that can still be followed
+    pub fn next_move(&self, game: &Game, queue: &[Piece]) -> Option<PieceLocation> {
+        self.openers.iter().find_map(|o| o.plan(game, queue)?.into_iter().next())
+    }
+}
+
+fn sorted(cells: [(i8, i8); 4]) -> [(i8, i8); 4] {
+    let mut cells = cells;
+    cells.sort();
+    cells
+}
+
+const fn mirror(piece: Piece) -> Piece {
+    match piece {
+        Piece::L => Piece::J,
+        Piece::J => Piece::L,
+        Piece::S => Piece::Z,
+        Piece::Z => Piece::S,
+        piece => piece
+    }
+}
+
+// the location of a piece covering exactly these cells
+fn locate(piece: Piece, cells: &[(i8, i8)]) -> Option<PieceLocation> {
+    if cells.len() != 4 {
+        return None;
+    }
+    let target = sorted([cells[0], cells[1], cells[2], cells[3]]);
+    for rotation in ROT {
+        for &(cx, cy) in cells {
+            for (bx, by) in (PieceLocation { piece, x: 0, y: 0, rotation, spin: Spin::None }).blocks() {
+                let loc = PieceLocation { piece, x: cx - bx, y: cy - by, rotation, spin: Spin::None };
+                if sorted(loc.blocks()) == target {
+                    return Some(loc);
+                }
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,180 @@
+use std::collections::HashSet;
+use utils::{
+    data::{Board, Piece, PieceLocation},
+    game::{Game, GameConfig},
+    movegen::movegen_piece
+};
+
+// perfect clear finder. tries every order the queue and hold allow, keeping every piece under a fixed height until
+// all of those lines are cleared. the search gives up on a branch early when:
+// - some placement would go over the height
+// - the empty cells left of a column boundary that nothing can cross (every row is filled on one side of it) don't
+//   come in whole pieces
+// - the pieces left can't even out the empty cells in even and odd columns. line clears take 5 of each, so this
+//   holds no matter when lines are cleared: o, s, z and flat i and t are balanced, l and j are always 2 off, t can
+//   be 2 off and i 4 off
+
+pub const MIN_HEIGHT: usize = 2;
+pub const MAX_HEIGHT: usize = 6;
+
+// the number of pieces a pc of this height takes, if the board fits under it at all
+pub fn pieces_needed(board: &Board, height: usize) -> Option<usize> {
+    let cells = board.cols.iter().map(|c| c.count_ones() as usize).sum::<usize>();
+    if board.max_height() as usize > height || cells > height * 10 || !(height * 10 - cells).is_multiple_of(4) {
+        return None;
+    }
+    Some((height * 10 - cells) / 4)
+}
+
+// whether there's a height up to max_height with few enough cells on the board for the queue to fill it
+pub fn plausible(game: &Game, queue: &[Piece], max_height: usize) -> bool {
+    (MIN_HEIGHT..=max_height).any(|h| pieces_needed(&game.board, h).is_some_and(|n| n > 0 && n <= queue.len()))
+}
+
+// every placement sequence that perfect clears at this height. the first piece of each is either queue[0] or the one
+// played out of hold, like search results
+pub fn find_pcs(game: &Game, queue: &[Piece], height: usize) -> Vec<Vec<PieceLocation>> {
+    let mut solver = Solver::new(&game.config, usize::MAX);
+    solver.solve(game, queue, height);
+    solver.found
+}
+
+// every pc from the lowest height up to max_height, lower ones first
+pub fn all_pcs(game: &Game, queue: &[Piece], max_height: usize) -> Vec<Vec<PieceLocation>> {
+    (MIN_HEIGHT..=max_height).flat_map(|h| find_pcs(game, queue, h)).collect()
+}
+
+// the first pc found, trying lower heights first
+pub fn first_pc(game: &Game, queue: &[Piece], max_height: usize) -> Option<Vec<PieceLocation>> {
+    let mut solver = Solver::new(&game.config, 1);
+    (MIN_HEIGHT..=max_height).find(|&h| {
+        solver.solve(game, queue, h);
+        !solver.found.is_empty()
+    })?;
+    solver.found.pop()
+}
+
+struct Solver<'a> {
+    config: &'a GameConfig,
+    limit: usize,
+    found: Vec<Vec<PieceLocation>>,
+    path: Vec<PieceLocation>,
+    // (board, hold, pieces left in the queue, height left) of positions with no pc
+    dead: HashSet<([u64; 10], u8, usize, usize)>,
+    arenas: Vec<Vec<PieceLocation>>
+}
+
+impl<'a> Solver<'a> {
+    fn new(config: &'a GameConfig, limit: usize) -> Self {
+        Self { config, limit, found: vec![], path: vec![], dead: HashSet::new(), arenas: vec![] }
+    }
+
+    fn solve(&mut self, game: &Game, queue: &[Piece], height: usize) {
+        let Some(n) = pieces_needed(&game.board, height) else { return };
+        if n == 0 || n > queue.len() {
+            return;
+        }
+        self.dead.clear();
+        self.dfs(&game.board, game.hold, queue, height, n);
+    }
+
+    // returns whether anything was found from here
+    fn dfs(&mut self, board: &Board, hold: Option<Piece>, queue: &[Piece], height: usize, n: usize) -> bool {
+        if height == 0 {
+            self.found.push(self.path.clone());
+            return true;
+        }
+        if n > queue.len() || !splits_ok(board, height) || !parity_ok(board, height, hold, queue, n) {
+            return false;
+        }
+        let key = (board.cols, hold.map_or(0, |p| p as u8 + 1), queue.len(), height);
+        if self.dead.contains(&key) {
+            return false;
+        }
+
+        // (piece to place, queue after it, hold after it)
+        let mut options = vec![(queue[0], &queue[1..], hold)];
+        match hold {
+            Some(h) if h != queue[0] => options.push((h, &queue[1..], Some(queue[0]))),
+            None if queue.len() > 1 && queue[1] != queue[0] => options.push((queue[1], &queue[2..], Some(queue[0]))),
+            _ => {}
+        }
+
+        let mut any = false;
+        let mut arena = self.arenas.pop().unwrap_or_default();
+        for (piece, rest, hold) in options {
+            arena.clear();
+            movegen_piece(&mut arena, board, piece, true, self.config);
+            let mut seen: Vec<[(i8, i8); 4]> = vec![];
+            for loc in &arena {
+                let mut cells = loc.blocks();
+                if cells.iter().any(|&(_, y)| y as usize >= height) {
+                    continue;
+                }
+                // the same cells reached with a different spin are the same pc
+                cells.sort();
+                if seen.contains(&cells) {
+                    continue;
+                }
+                seen.push(cells);
+
+                let mut next = *board;
+                next.put_piece(loc);
+                let cleared = next.remove_lines().count_ones() as usize;
+                self.path.push(loc.clone());
+                any |= self.dfs(&next, hold, rest, height - cleared, n - 1);
+                self.path.pop();
+                if self.found.len() >= self.limit {
+                    self.arenas.push(arena);
+                    return true;
+                }
+            }
+        }
+        self.arenas.push(arena);
+        if !any {
+            self.dead.insert(key);
+        }
+        any
+    }
+}
+
+// empty cells under the height in each column
+fn empty_cells(board: &Board, height: usize) -> [usize; 10] {
+    let mask = (1u64 << height) - 1;
+    board.cols.map(|c| height - (c & mask).count_ones() as usize)
+}
+
+fn splits_ok(board: &Board, height: usize) -> bool {
+    let mask = (1u64 << height) - 1;
+    let empty = empty_cells(board, height);
+    let mut left = 0;
+    (0..9).all(|x| {
+        left += empty[x];
+        let wall = (board.cols[x] | board.cols[x + 1]) & mask == mask;
+        !wall || left.is_multiple_of(4)
+    })
+}
+
+fn parity_ok(board: &Board, height: usize, hold: Option<Piece>, queue: &[Piece], n: usize) -> bool {
+    let empty = empty_cells(board, height);
+    let diff = (0..10).map(|x| if x % 2 == 0 { empty[x] as i32 } else { -(empty[x] as i32) }).sum::<i32>() / 2;
+
+    // the pieces that can still be played. one more than needed means any one of them can stay in hold
+    let pool: Vec<Piece> = hold.iter().chain(queue).take(n + 1).copied().collect();
+    let fits = |pieces: &mut dyn Iterator<Item = &Piece>| {
+        let (mut lj, mut t, mut i) = (0, 0, 0);
+        for p in pieces {
+            match p {
+                Piece::L | Piece::J => lj += 1,
+                Piece::T => t += 1,
+                Piece::I => i += 1,
+                _ => {}
+            }
+        }
+        diff.abs() <= lj + t + 2 * i && (t > 0 || (diff - lj) % 2 == 0)
+    };
+    if pool.len() == n {
+        return fits(&mut pool.iter());
+    }
+    (0..pool.len()).any(|skip| fits(&mut pool.iter().enumerate().filter(|&(i, _)| i != skip).map(|(_, p)| p)))
+}
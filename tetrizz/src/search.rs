@@ -1,100 +1,202 @@
 use std::collections::BinaryHeap;
 
+use serde::Deserialize;
 use utils::{
-    game::Game,
-    data::{Piece, PieceLocation, Spin},
+    game::{GarbageSpeculation, Game, GameState, PlacementInfo},
+    data::{Board, Piece, PieceLocation},
     movegen::movegen
 };
 use crate::eval::base::Eval;
 
 
 #[derive(Clone, Debug)]
-pub struct Node {
-    pub game: Game,
+pub struct Node<'a> {
+    pub game: GameState<'a>,
     pub id: usize,
-    pub score: f64
+    pub score: f64,
+    // index of the node this was expanded from in the previous layer, and the placement that got here from it
+    pub parent: usize,
+    pub loc: PieceLocation,
+    // index in the queue of the next piece to place. holding with an empty hold moves it on by two
+    pub cursor: usize
 }
 
-impl PartialEq for Node {
+impl PartialEq for Node<'_> {
     fn eq(&self, other: &Self) -> bool {
         self.score == other.score
     }
 }
 
-impl Eq for Node {  }
+impl Eq for Node<'_> {  }
 
-impl PartialOrd for Node {
+impl PartialOrd for Node<'_> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         other.score.partial_cmp(&self.score)
     }
 }
 
-impl Ord for Node {
+impl Ord for Node<'_> {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         self.partial_cmp(other).unwrap()
     }
 }
 
-pub fn search(root: &Game, queue: &Vec<Piece>, eval: &impl Eval, depth: usize, width: usize) -> Option<PieceLocation> {
+// the other player, so search can guess what they are about to send
+#[derive(Debug, Clone, Deserialize)]
+pub struct Opponent {
+    pub game: Game,
+    pub queue: Vec<Piece>
+}
+
+// their search is only there to estimate attack, so it can be a lot narrower than ours
+const OPPONENT_WIDTH: usize = 200;
+
+pub fn search(root: &Game, queue: &[Piece], eval: &impl Eval, depth: usize, width: usize) -> Option<PieceLocation> {
+    search_pv(root, queue, eval, depth, width).into_iter().next()
+}
+
+// the best line found, starting with the move to play now. empty if there is no move
+pub fn search_pv(root: &Game, queue: &[Piece], eval: &impl Eval, depth: usize, width: usize) -> Vec<PieceLocation> {
+    search_pv_vs(root, queue, eval, depth, width, None)
+}
+
+pub fn search_vs(root: &Game, queue: &[Piece], eval: &impl Eval, depth: usize, width: usize, opponent: Option<&Opponent>) -> Option<PieceLocation> {
+    search_pv_vs(root, queue, eval, depth, width, opponent).into_iter().next()
+}
+
+// garbage the opponent is expected to send with each of their next placements, from a quick search of their position
+// with our eval. shorter than depth if their queue is, if holding uses it up, or if they have no moves left
+pub fn expected_attack(opponent: &Opponent, eval: &impl Eval, depth: usize) -> Vec<u16> {
+    let depth = depth.min(opponent.queue.len());
+    if depth == 0 {
+        return vec![];
+    }
+    let pv = search_pv(&opponent.game, &opponent.queue, eval, depth, OPPONENT_WIDTH);
+    let mut game = opponent.game.state();
+    let mut cursor = 0;
+    pv.iter().map(|loc| {
+        let info;
+        (game, info, cursor) = place(&game, &opponent.queue, cursor, loc, expected_hole(&game.board));
+        info.outgoing_attack
+    }).collect()
+}
+
+// the piece a node has to place, and the one hold would give instead (the held piece, or with nothing held the one after
+// it). once the queue runs out the held piece is still known, so it can be placed last
+pub fn pieces(queue: &[Piece], cursor: usize, hold: Option<Piece>) -> Option<(Piece, Option<Piece>)> {
+    match queue.get(cursor) {
+        Some(&current) => Some((current, hold.or(queue.get(cursor + 1).copied()).filter(|&h| h != current))),
+        None => hold.map(|h| (h, None))
+    }
+}
+
+// the state after loc, one of the placements pieces() allows, with any garbage tanked at hole, and the cursor after it
+pub fn place<'a>(game: &GameState<'a>, queue: &[Piece], cursor: usize, loc: &PieceLocation, hole: usize) -> (GameState<'a>, PlacementInfo, usize) {
+    let Some(&current) = queue.get(cursor) else {
+        let (after, info) = GameState { hold: None, ..*game }.after(loc.piece, loc, hole);
+        return (after, info, cursor);
+    };
+    let (after, info) = game.after(current, loc, hole);
+    let cursor = if loc.piece != current && game.hold.is_none() { cursor + 2 } else { cursor + 1 };
+    (after, info, cursor)
+}
+
+// the hole in the bottom row if it's a garbage line, else column 0
+pub fn expected_hole(board: &Board) -> usize {
+    let mut gaps = (0..10).filter(|&x| board.cols[x] & 1 == 0);
+    match (gaps.next(), gaps.next()) {
+        (Some(x), None) => x,
+        _ => 0
+    }
+}
+
+// a search node's child: loc placed after incoming garbage arrives, with the score the eval gives it. the child carries
+// on with the expected hole, sampling only spreads the hole out for the score. None if the eval prunes it
+fn expand<'a>(parent: &GameState<'a>, incoming: u16, queue: &[Piece], cursor: usize, loc: &PieceLocation, eval: &impl Eval) -> Option<(GameState<'a>, usize, f64)> {
+    let parent = GameState { incoming_garbage: parent.incoming_garbage.saturating_add(incoming), ..*parent };
+    let (game, info, next) = place(&parent, queue, cursor, loc, expected_hole(&parent.board));
+    if !eval.allows(&info) {
+        return None;
+    }
+    let mut score = eval.value(&game, &info);
+    // where the hole goes only matters if garbage came up
+    if let GarbageSpeculation::Sampled(n) = parent.config.speculation && n > 1 && info.lines_received > 0 {
+        let samples = n.min(10) as usize;
+        score = (0..samples).map(|i| {
+            let (other, info, _) = place(&parent, queue, cursor, loc, i * 10 / samples);
+            eval.value(&other, &info)
+        }).sum::<f64>() / samples as f64;
+    }
+    Some((game, next, score))
+}
+
+// search_pv, with the opponent's expected attack arriving before each of our placements: a line clear cancels it, and
+// the next placement that doesn't clear tanks it, so the eval sees spikes coming in time to block or outrun them
+pub fn search_pv_vs(root: &Game, queue: &[Piece], eval: &impl Eval, depth: usize, width: usize, opponent: Option<&Opponent>) -> Vec<PieceLocation> {
     assert!(queue.len() >= depth);
+    let incoming = opponent.map_or(vec![], |o| expected_attack(o, eval, depth));
+    let incoming = |idx: usize| incoming.get(idx).copied().unwrap_or(0);
+    let root = root.state();
 
     let mut heap: BinaryHeap<Node> = BinaryHeap::with_capacity(width + 1);
     let mut next: BinaryHeap<Node> = BinaryHeap::with_capacity(width + 1);
     let mut arena: Vec<PieceLocation> = vec![];
-    let mut search_positions: Vec<PieceLocation> = vec![];
+    let mut layers: Vec<Vec<Node>> = vec![];
 
-    movegen(&mut arena, &root.board, queue[0], Some(root.hold.unwrap_or_else(|| queue[1])), true);
+    let Some((current, hold)) = pieces(queue, 0, root.hold) else {
+        return vec![];
+    };
+    movegen(&mut arena, &root.board, current, hold, true, root.config);
     
     for (id, loc) in arena[..].iter().enumerate() {
-        let mut game = root.clone();
-        let placement_info = game.advance(queue[0], loc);
-        search_positions.push(loc.clone());
-        if placement_info.lines_cleared > 1 || (placement_info.lines_cleared == 1 && placement_info.spin == Spin::None) {
+        let Some((game, cursor, score)) = expand(&root, incoming(0), queue, 0, loc, eval) else {
             continue;
-        }
-        if !game.can_spawn_piece(queue[1]) {
+        };
+        if let Some((n, _)) = pieces(queue, cursor, game.hold) && !game.can_spawn_piece(n) {
             continue;
         }
-        let score = eval.value(&game, &placement_info);
-        insert_if_better(&mut heap, Node { game, id, score }, width);
+        insert_if_better(&mut heap, Node { game, id, score, parent: id, loc: loc.clone(), cursor }, width);
     }
     
     for idx in 1..depth {
-        for node in &heap {
-            let current_piece = queue.get(idx).copied().or(node.game.hold);
-            if current_piece.is_none() {
-                break;
-            }
-            let current_piece = current_piece.unwrap();
-            let next_piece = queue.get(idx + 1).copied();
-            let start = movegen(&mut arena, &node.game.board, current_piece, node.game.hold.or(next_piece), true);
+        for (parent, node) in heap.iter().enumerate() {
+            // this node has used up the queue, hold included
+            let Some((current, hold)) = pieces(queue, node.cursor, node.game.hold) else {
+                continue;
+            };
+            let start = movegen(&mut arena, &node.game.board, current, hold, true, node.game.config);
             for loc in &arena[start..] {
-                let mut game = node.game.clone();
-                let placement_info = game.advance(current_piece, &loc);
-                if placement_info.lines_cleared > 1 || (placement_info.lines_cleared == 1 && placement_info.spin == Spin::None) {
+                let Some((game, cursor, score)) = expand(&node.game, incoming(idx), queue, node.cursor, loc, eval) else {
                     continue;
-                }
-                if let Some(n) = next_piece && !game.can_spawn_piece(n) {
+                };
+                if let Some((n, _)) = pieces(queue, cursor, game.hold) && !game.can_spawn_piece(n) {
                     continue;
                 }
-                let score = eval.value(&game, &placement_info);
-                insert_if_better(&mut next, Node { game, id: node.id, score }, width);
+                insert_if_better(&mut next, Node { game, id: node.id, score, parent, loc: loc.clone(), cursor }, width);
             }
         }
-        if next.len() == 0 {
+        if next.is_empty() {
             break;
         }
-        heap.clear();
+        // iter and into_vec both go through the heap's backing vec in the same order, so parent indices stay valid
         std::mem::swap(&mut heap, &mut next);
+        layers.push(std::mem::replace(&mut next, BinaryHeap::with_capacity(width + 1)).into_vec());
     }
 
-    if let Some(m) = heap.into_iter().min() {
-        return Some(search_positions[m.id].clone());
+    let Some(best) = heap.into_iter().min() else {
+        return vec![];
+    };
+    let mut parent = best.parent;
+    let mut pv = vec![best.loc];
+    for layer in layers.iter().rev() {
+        pv.push(layer[parent].loc.clone());
+        parent = layer[parent].parent;
     }
-    None
+    pv.reverse();
+    pv
 }
 
-fn insert_if_better(heap: &mut BinaryHeap<Node>, node: Node, width: usize) {
+fn insert_if_better<'a>(heap: &mut BinaryHeap<Node<'a>>, node: Node<'a>, width: usize) {
     if heap.len() < width {
         heap.push(node);
     } else if let Some(worst) = heap.peek() {
@@ -0,0 +1,168 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard}
+};
+use serde::{Serialize, Deserialize};
+use utils::{
+    data::{Piece, PieceLocation},
+    game::Game,
+    input::{Finesse, MovementAction, keygen}
+};
+use crate::{
+    error::BotError,
+    eval::base::Eval,
+    search::search_pv
+};
+
+// the json api behind the server binary. every room is its own session with a game, a queue and search settings; the
+// same requests and responses are used over http and websockets
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionConfig {
+    pub depth: usize,
+    pub width: usize,
+    // human-like keys (das, fewest presses) instead of taps only
+    pub human: bool
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self { depth: 6, width: 2000, human: false }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    // replaces the game and queue. the first piece of the queue is the one to place next
+    State {
+        game: Game,
+        queue: Vec<Piece>
+    },
+    Config(SessionConfig),
+    GetState,
+    Suggest,
+    Pv
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response {
+    Ok,
+    State {
+        game: Game,
+        queue: Vec<Piece>,
+        config: SessionConfig
+    },
+    // keys start with a Hold if the move comes out of hold, and end with the Harddrop
+    Suggestion {
+        location: PieceLocation,
+        keys: Vec<MovementAction>
+    },
+    Pv {
+        moves: Vec<PieceLocation>
+    },
+    Error {
+        kind: &'static str,
+        message: String
+    }
+}
+
+impl From<BotError> for Response {
+    fn from(e: BotError) -> Self {
+        Response::Error { kind: e.kind(), message: e.to_string() }
+    }
+}
+
+pub struct Session<E: Eval> {
+    pub game: Game,
+    pub queue: Vec<Piece>,
+    pub config: SessionConfig,
+    pub eval: E
+}
+
+impl<E: Eval> Session<E> {
+    pub fn new(eval: E) -> Self {
+        Self { game: Game::new_empty(), queue: vec![], config: SessionConfig::default(), eval }
+    }
+
+    pub fn handle(&mut self, request: Request) -> Response {
+        self.try_handle(request).unwrap_or_else(Response::from)
+    }
+
+    fn try_handle(&mut self, request: Request) -> Result<Response, BotError> {
+        Ok(match request {
+            Request::State { game, queue } => {
+                self.game = game;
+                self.queue = queue;
+                Response::Ok
+            }
+            Request::Config(config) => {
+                self.config = config;
+                Response::Ok
+            }
+            Request::GetState => Response::State { game: self.game.clone(), queue: self.queue.clone(), config: self.config.clone() },
+            Request::Suggest => {
+                let location = self.pv()?.into_iter().next().ok_or(BotError::NoMove)?;
+                let finesse = if self.config.human { Finesse::Keys } else { Finesse::Taps };
+                let mut keys = keygen(&self.game.board, &location, &finesse, &self.game.config)?;
+                if location.piece != self.queue[0] {
+                    keys.insert(0, MovementAction::Hold);
+                }
+                Response::Suggestion { location, keys }
+            }
+            Request::Pv => Response::Pv { moves: self.pv()? }
+        })
+    }
+
+    fn pv(&self) -> Result<Vec<PieceLocation>, BotError> {
+        if self.queue.is_empty() {
+            return Err(BotError::EmptyQueue);
+        }
+        Ok(search_pv(&self.game, &self.queue, &self.eval, self.config.depth.min(self.queue.len()), self.config.width))
+    }
+}
+
+// sessions by room name. rooms are locked separately, so searches in different rooms run at the same time
+pub struct Rooms<E: Eval + Clone> {
+    eval: E,
+    rooms: Mutex<HashMap<String, Arc<Mutex<Session<E>>>>>
+}
+
+impl<E: Eval + Clone> Rooms<E> {
+    // new rooms start with an empty game and this eval
+    pub fn new(eval: E) -> Self {
+        Self { eval, rooms: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn session(&self, room: &str) -> Arc<Mutex<Session<E>>> {
+        lock(&self.rooms)
+            .entry(room.to_string())
+            .or_insert_with(|| Arc::new(Mutex::new(Session::new(self.eval.clone()))))
+            .clone()
+    }
+
+    pub fn remove(&self, room: &str) -> bool {
+        lock(&self.rooms).remove(room).is_some()
+    }
+
+    pub fn handle(&self, room: &str, request: Request) -> Response {
+        let session = self.session(room);
+        let mut session = lock(&session);
+        session.handle(request)
+    }
+
+    pub fn handle_json(&self, room: &str, text: &str) -> String {
+        let response = match serde_json::from_str(text) {
+            Ok(request) => self.handle(room, request),
+            Err(e) => BotError::Json(e).into()
+        };
+        serde_json::to_string(&response).unwrap()
+    }
+}
+
+// a panic in one request shouldn't take the room down with it
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
@@ -0,0 +1,131 @@
+use serde::Serialize;
+use utils::{
+    cheese::Cheese,
+    data::Spin,
+    game::Game,
+    queue::SeededBag
+};
+use crate::{
+    battle::GarbageSource,
+    eval::base::Eval,
+    modes::ModeSettings,
+    search::search
+};
+
+// survival benchmark: the bot plays alone against a garbage source and attacks into nothing, so its attack, b2b and
+// digging can be measured the same way every run
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SurvivalStats {
+    pub seed: u64,
+    pub pieces: usize,
+    pub attack: u32,
+    pub lines: u32,
+    // garbage lines that made it onto the board, and how many of those were cleared again
+    pub garbage_received: u32,
+    pub garbage_cleared: u32,
+    // line clears by spin type
+    pub spins: u32,
+    pub minis: u32,
+    pub quads: u32,
+    pub pcs: u32,
+    // length of every b2b chain, in clears, including one still going at the end
+    pub b2b_chains: Vec<u32>,
+    pub topped_out: bool
+}
+
+impl SurvivalStats {
+    pub fn app(&self) -> f32 {
+        self.attack as f32 / self.pieces.max(1) as f32
+    }
+
+    pub fn apm(&self, pps: f32) -> f32 {
+        self.app() * pps * 60.0
+    }
+
+    // seconds survived
+    pub fn time(&self, pps: f32) -> f32 {
+        self.pieces as f32 / pps
+    }
+
+    // pieces it took to clear each garbage line
+    pub fn pieces_per_garbage(&self) -> f32 {
+        self.pieces as f32 / self.garbage_cleared.max(1) as f32
+    }
+
+    pub fn max_b2b(&self) -> u32 {
+        self.b2b_chains.iter().copied().max().unwrap_or(0)
+    }
+
+    pub const CSV_HEADER: &str = "seed,pieces,seconds,attack,app,apm,lines,garbage_received,garbage_cleared,pieces_per_garbage,spins,minis,quads,pcs,b2b_chains,max_b2b,topped_out";
+
+    pub fn csv_row(&self, pps: f32) -> String {
+        format!(
+            "{},{},{:.2},{},{:.4},{:.2},{},{},{},{:.4},{},{},{},{},{},{},{}",
+            self.seed, self.pieces, self.time(pps), self.attack, self.app(), self.apm(pps), self.lines, self.garbage_received,
+            self.garbage_cleared, self.pieces_per_garbage(), self.spins, self.minis, self.quads, self.pcs, self.b2b_chains.len(),
+            self.max_b2b(), self.topped_out
+        )
+    }
+}
+
+// plays until the bot tops out or settings.max_pieces. settings.finesse isn't used, keys aren't generated
+pub fn survive(source: &mut impl GarbageSource, settings: &ModeSettings, eval: &impl Eval, seed: u64) -> SurvivalStats {
+    let mut bag = SeededBag::new(seed);
+    let mut holes = Cheese::new(seed, 1.0);
+    let mut game = Game::new_empty();
+    let mut queue = vec![];
+    let mut stats = SurvivalStats { seed, ..Default::default() };
+    // garbage rows at the bottom of the board. everything placed goes on top of them, so they stay together
+    let mut garbage = 0;
+
+    while stats.pieces < settings.max_pieces {
+        while queue.len() < settings.depth + 2 {
+            bag.extend(&mut queue, 1);
+        }
+        let Some(loc) = search(&game, &queue[..settings.depth + 1], eval, settings.depth, settings.width) else {
+            stats.topped_out = true;
+            break;
+        };
+
+        let mut board = game.board;
+        board.put_piece(&loc);
+        let full = (0..10).fold(u64::MAX, |acc, x| acc & board.cols[x]);
+        let cleared = (full & 1u64.checked_shl(garbage).map_or(u64::MAX, |b| b - 1)).count_ones();
+
+        let current = queue.remove(0);
+        if loc.piece != current && game.hold.is_none() {
+            queue.remove(0);
+        }
+        let b2b = game.b2b;
+        let info = game.advance_with(current, &loc, || holes.next_hole());
+        game.incoming_garbage = game.incoming_garbage.saturating_add(source.next(info.outgoing_attack));
+        stats.pieces += 1;
+        stats.attack += info.outgoing_attack as u32;
+        stats.lines += info.lines_cleared as u32;
+        stats.garbage_cleared += cleared;
+        stats.garbage_received += info.lines_received as u32;
+        garbage = garbage - cleared + info.lines_received as u32;
+        if info.lines_cleared > 0 {
+            match info.spin {
+                Spin::Full => stats.spins += 1,
+                Spin::Mini => stats.minis += 1,
+                Spin::None => {}
+            }
+            stats.quads += (info.lines_cleared == 4) as u32;
+            stats.pcs += info.pc as u32;
+        }
+        if b2b >= 0 && game.b2b < 0 {
+            stats.b2b_chains.push(b2b as u32 + 1);
+        }
+        // topping out on garbage, which search doesn't see coming
+        if game.board.max_height() > 40 {
+            stats.topped_out = true;
+            break;
+        }
+    }
+    if game.b2b >= 0 {
+        stats.b2b_chains.push(game.b2b as u32 + 1);
+    }
+    stats
+}
@@ -0,0 +1,248 @@
+use serde::{Serialize, Deserialize};
+use utils::{
+    data::{Board, Piece, PieceError, PieceLocation, Rotation, Spin},
+    game::Game
+};
+use crate::{
+    error::BotError,
+    eval::base::Eval,
+    opener::Book,
+    pc::{first_pc, plausible},
+    search::search
+};
+
+// tetris bot protocol (https://github.com/tetris-bot-protocol/tbp-spec). positions are the same as ours: x and y of the
+// piece's center block, y up, row 0 at the bottom
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FrontendMessage {
+    Rules {
+        #[serde(default)]
+        randomizer: Option<String>
+    },
+    Start(Start),
+    Stop,
+    Suggest,
+    Play {
+        #[serde(rename = "move")]
+        mv: Move
+    },
+    NewPiece {
+        piece: Piece
+    },
+    Quit,
+    // the spec says to ignore messages we don't know
+    #[serde(other)]
+    Unknown
+}
+
+impl FrontendMessage {
+    // what to send back when handling this message failed. tbp has no error message outside of rules, but a frontend
+    // that asked for a suggestion waits for one, so it gets an empty one, the same as when there's no move
+    pub fn error_reply(&self) -> Option<BotMessage> {
+        match self {
+            FrontendMessage::Suggest => Some(BotMessage::Suggestion { moves: vec![] }),
+            _ => None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Start {
+    pub hold: Option<Piece>,
+    pub queue: Vec<Piece>,
+    pub combo: u32,
+    pub back_to_back: bool,
+    // 40 rows of 10 cells, bottom row first. cells are null or a piece/garbage letter
+    pub board: Vec<Vec<Option<char>>>
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BotMessage {
+    Info {
+        name: &'static str,
+        version: &'static str,
+        author: &'static str,
+        features: Vec<&'static str>
+    },
+    Ready,
+    Suggestion {
+        moves: Vec<Move>
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Move {
+    pub location: Location,
+    #[serde(default)]
+    pub spin: TbpSpin
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Location {
+    #[serde(rename = "type")]
+    pub piece: Piece,
+    pub orientation: Orientation,
+    pub x: i8,
+    pub y: i8
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    North, East, South, West
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TbpSpin {
+    #[default]
+    None, Mini, Full
+}
+
+impl From<&PieceLocation> for Move {
+    fn from(loc: &PieceLocation) -> Self {
+        let orientation = match loc.rotation {
+            Rotation::North => Orientation::North,
+            Rotation::East => Orientation::East,
+            Rotation::South => Orientation::South,
+            Rotation::West => Orientation::West
+        };
+        let spin = match loc.spin {
+            Spin::None => TbpSpin::None,
+            Spin::Mini => TbpSpin::Mini,
+            Spin::Full => TbpSpin::Full
+        };
+        Move { location: Location { piece: loc.piece, orientation, x: loc.x, y: loc.y }, spin }
+    }
+}
+
+impl From<&Move> for PieceLocation {
+    fn from(mv: &Move) -> Self {
+        let rotation = match mv.location.orientation {
+            Orientation::North => Rotation::North,
+            Orientation::East => Rotation::East,
+            Orientation::South => Rotation::South,
+            Orientation::West => Rotation::West
+        };
+        let spin = match mv.spin {
+            TbpSpin::None => Spin::None,
+            TbpSpin::Mini => Spin::Mini,
+            TbpSpin::Full => Spin::Full
+        };
+        PieceLocation { piece: mv.location.piece, x: mv.location.x, y: mv.location.y, rotation, spin }
+    }
+}
+
+impl Start {
+    pub fn to_game(&self) -> Result<Game, BotError> {
+        if self.board.len() > 64 {
+            return Err(BotError::Board(format!("{} rows, at most 64 fit", self.board.len())));
+        }
+        let mut board = Board { cols: [0; 10] };
+        for (y, row) in self.board.iter().enumerate() {
+            if row.len() != 10 {
+                return Err(BotError::Board(format!("row {y} has {} cells, expected 10", row.len())));
+            }
+            for (x, cell) in row.iter().enumerate() {
+                match cell {
+                    None => {}
+                    Some(c) if *c == 'G' || Piece::from_letter(*c).is_some() => board.cols[x] |= 1 << y,
+                    Some(c) => return Err(BotError::Piece(PieceError(*c)))
+                }
+            }
+        }
+        Ok(Game {
+            board,
+            hold: self.hold,
+            b2b: if self.back_to_back { 0 } else { -1 },
+            combo: self.combo.min(i8::MAX as u32) as i8 - 1,
+            ..Game::new_empty()
+        })
+    }
+}
+
+// one game as seen through tbp: the state between start and stop, and the search settings used for suggestions
+pub struct Bot<E: Eval> {
+    pub eval: E,
+    pub depth: usize,
+    pub width: usize,
+    // followed before searching, for as long as one of its openers fits
+    pub book: Book,
+    // perfect clears up to this many lines are looked for before searching. 0 turns it off
+    pub pc_height: usize,
+    state: Option<(Game, Vec<Piece>)>
+}
+
+impl<E: Eval> Bot<E> {
+    pub fn new(eval: E, depth: usize, width: usize) -> Self {
+        Self { eval, depth, width, book: Book::default(), pc_height: 4, state: None }
+    }
+
+    pub fn info() -> BotMessage {
+        BotMessage::Info {
+            name: "tetrizz",
+            version: env!("CARGO_PKG_VERSION"),
+            author: "Tyrcnex",
+            features: vec![]
+        }
+    }
+
+    // handles one message, returning the reply if the message needs one. messages that don't make sense in the current
+    // state are errors; the bot's state is left untouched so the frontend can carry on
+    pub fn handle(&mut self, msg: FrontendMessage) -> Result<Option<BotMessage>, BotError> {
+        match msg {
+            FrontendMessage::Rules { .. } => Ok(Some(BotMessage::Ready)),
+            FrontendMessage::Start(start) => {
+                self.state = Some((start.to_game()?, start.queue));
+                Ok(None)
+            }
+            FrontendMessage::Stop => {
+                self.state = None;
+                Ok(None)
+            }
+            FrontendMessage::Suggest => {
+                let (game, queue) = self.state.as_ref().ok_or(BotError::NoGame)?;
+                if queue.is_empty() {
+                    return Ok(Some(BotMessage::Suggestion { moves: vec![] }));
+                }
+                // tbp tells bots nothing about the opponent, so there's no incoming attack to search against
+                let found = self.book.next_move(game, queue)
+                    .or_else(|| self.pc(game, queue))
+                    .or_else(|| search(game, queue, &self.eval, self.depth.min(queue.len()), self.width));
+                Ok(Some(BotMessage::Suggestion { moves: found.iter().map(Move::from).collect() }))
+            }
+            FrontendMessage::Play { mv } => {
+                let (game, queue) = self.state.as_mut().ok_or(BotError::NoGame)?;
+                let loc = PieceLocation::from(&mv);
+                let current = *queue.first().ok_or(BotError::EmptyQueue)?;
+                // the played piece is either the current one, or whatever comes out of hold (the next piece if hold is empty)
+                let held = game.hold.or(queue.get(1).copied());
+                if (loc.piece != current && Some(loc.piece) != held) || game.board.collides(&loc) {
+                    return Err(BotError::IllegalMove(loc));
+                }
+                if loc.piece != current && game.hold.is_none() {
+                    queue.remove(0);
+                }
+                queue.remove(0);
+                game.advance(current, &loc);
+                Ok(None)
+            }
+            FrontendMessage::NewPiece { piece } => {
+                let (_, queue) = self.state.as_mut().ok_or(BotError::NoGame)?;
+                queue.push(piece);
+                Ok(None)
+            }
+            FrontendMessage::Quit | FrontendMessage::Unknown => Ok(None)
+        }
+    }
+
+    fn pc(&self, game: &Game, queue: &[Piece]) -> Option<PieceLocation> {
+        if !plausible(game, queue, self.pc_height) {
+            return None;
+        }
+        first_pc(game, queue, self.pc_height)?.into_iter().next()
+    }
+}
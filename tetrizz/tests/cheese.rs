@@ -0,0 +1,54 @@
+use tetrizz::{
+    cheese::{CheeseRace, run},
+    eval::{base::{Eval, MinimalEval}, downstack::DownstackEval}
+};
+use utils::{
+    data::Spin,
+    game::{Game, PlacementInfo}
+};
+
+fn race() -> CheeseRace {
+    CheeseRace { lines: 12, visible: 6, depth: 3, width: 100, max_pieces: 200, ..CheeseRace::default() }
+}
+
+#[test]
+fn downstack_clears_singles() {
+    let single = PlacementInfo { lines_cleared: 1, lines_received: 0, pc: false, b2b_clear: false, broke_surge: false, spin: Spin::None, outgoing_attack: 0 };
+    let eval = MinimalEval { values: [0.0; 15] };
+    assert!(!eval.allows(&single));
+    assert!(DownstackEval::default().allows(&single));
+}
+
+#[test]
+fn downstack_full_column() {
+    let single = PlacementInfo { lines_cleared: 1, lines_received: 0, pc: false, b2b_clear: false, broke_surge: false, spin: Spin::None, outgoing_attack: 0 };
+    let mut game = Game::new_empty();
+    game.board.cols[0] = u64::MAX;
+    game.board.cols[1] = u64::MAX - 1;
+    assert!(DownstackEval::default().value(&game.state(), &single).is_finite());
+}
+
+#[test]
+fn finishes_races() {
+    let race = race();
+    for seed in 0..3 {
+        let result = run(&race, &DownstackEval::default(), seed);
+        assert!(result.finished, "{result:?}");
+        assert_eq!(result.cleared, race.lines);
+        // every line needs at least 9 cells filled
+        assert!(result.pieces_per_line() >= 2.25 && result.pieces_per_line() < 5.0, "{result:?}");
+    }
+
+    // same seed, same race
+    let a = run(&race, &DownstackEval::default(), 5);
+    let b = run(&race, &DownstackEval::default(), 5);
+    assert_eq!((a.pieces, a.cleared), (b.pieces, b.cleared));
+}
+
+#[test]
+fn gives_up() {
+    let race = CheeseRace { max_pieces: 5, ..race() };
+    let result = run(&race, &DownstackEval::default(), 0);
+    assert!(!result.finished);
+    assert_eq!(result.pieces, 5);
+}
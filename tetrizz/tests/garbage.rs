@@ -0,0 +1,120 @@
+use tetrizz::{
+    battle::{Battle, GarbageSource, Player, Side},
+    eval::downstack::DownstackEval,
+    garbage::{AttackLog, Pattern, Poisson, Scripted}
+};
+use utils::{
+    data::Piece,
+    queue::SeededBag,
+    replay::{PlayerReplay, ReplayEvent, ReplayOptions, TimedEvent}
+};
+
+fn sent(pattern: Pattern, pieces: usize, pps: f32) -> f32 {
+    (0..pieces).map(|p| pattern.lines(p, pps)).sum()
+}
+
+fn total(source: &mut impl GarbageSource, pieces: usize) -> Vec<u16> {
+    (0..pieces).map(|_| source.next(0)).collect()
+}
+
+#[test]
+fn patterns() {
+    assert!((sent(Pattern::Constant { apm: 60.0 }, 120, 2.0) - 60.0).abs() < 1e-3);
+    // bursts land at 15s and 30s, the piece that crosses them gets all of it
+    let burst = Pattern::Burst { lines: 8, every: 15.0 };
+    assert_eq!(sent(burst, 61, 2.0), 16.0);
+    assert_eq!((0..61).filter(|&p| burst.lines(p, 2.0) > 0.0).count(), 2);
+
+    let increasing = Pattern::Increasing { apm: 60.0, growth: 0.1, after: 10 };
+    assert_eq!(increasing.lines(5, 2.0), Pattern::Constant { apm: 60.0 }.lines(5, 2.0));
+    assert!((increasing.lines(20, 2.0) - 1.0).abs() < 1e-5);
+}
+
+#[test]
+fn scripted() {
+    // half a line a piece comes out as a line every other piece
+    let lines = total(&mut Scripted::new(Pattern::Constant { apm: 60.0 }, 2.0), 10);
+    assert_eq!(lines, [0, 1, 0, 1, 0, 1, 0, 1, 0, 1]);
+    let lines = total(&mut Scripted::new(Pattern::Burst { lines: 8, every: 15.0 }, 2.0), 61);
+    assert_eq!(lines.iter().sum::<u16>(), 16);
+}
+
+#[test]
+fn poisson() {
+    let lines = total(&mut Poisson::new(30.0, 4, 2.0, 1), 1200);
+    // 0.25 attacks a piece on average
+    let attacks = lines.iter().map(|&l| l / 4).sum::<u16>();
+    assert!((250..350).contains(&attacks), "{attacks}");
+    assert!(lines.iter().all(|l| l % 4 == 0));
+    assert_eq!(lines, total(&mut Poisson::new(30.0, 4, 2.0, 1), 1200));
+    assert_ne!(lines, total(&mut Poisson::new(30.0, 4, 2.0, 2), 1200));
+}
+
+#[test]
+fn attack_logs() {
+    let mut log = AttackLog::from_timed(&[(0.2, 2), (0.4, 1), (3.0, 4)], 2.0);
+    assert_eq!(total(&mut log, 8), [3, 0, 0, 0, 0, 0, 4, 0]);
+
+    let garbage = |frame, amount| TimedEvent { frame, subframe: 0.0, event: ReplayEvent::Garbage { amount, column: None } };
+    let replay = PlayerReplay {
+        username: None,
+        round: 0,
+        options: ReplayOptions {
+            seed: 0,
+            das: 10.0,
+            arr: 2.0,
+            sdf: 6.0,
+            gravity: 0.02,
+            lock_delay: 30,
+            next_count: 5,
+            rotation: Default::default(),
+            spins: Default::default()
+        },
+        events: vec![garbage(60, 3), garbage(90, 1), garbage(600, 5)]
+    };
+    assert_eq!(AttackLog::from_replay(&replay, 1.0).lines, [0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 5]);
+}
+
+fn player(queue: &[Piece]) -> Player<DownstackEval> {
+    Player { queue: queue.to_vec(), depth: 3, width: 200, ..Player::new(DownstackEval::default()) }
+}
+
+#[test]
+fn against_source() {
+    let mut queue = vec![];
+    SeededBag::new(4).extend(&mut queue, 10);
+    let mut battle = Battle::new(player(&queue), AttackLog::new(vec![0, 3]));
+    let mut expected = queue.clone();
+    for _ in 0..2 {
+        let empty_hold = battle.player.game.hold.is_none();
+        let (loc, _) = battle.advance().unwrap();
+        // the piece comes off the queue, and so does the one after it when it goes into an empty hold
+        let current = expected.remove(0);
+        if loc.piece != current && empty_hold {
+            expected.remove(0);
+        }
+    }
+    assert_eq!(battle.player.queue, expected);
+    // sent with the second piece, tanked with the next one that doesn't clear
+    assert_eq!(battle.player.game.incoming_garbage, 3);
+
+    // the queue running out ends it
+    battle.player.queue.clear();
+    assert_eq!(battle.advance().err(), Some(Side::Player));
+}
+
+#[test]
+fn against_bot() {
+    let mut queue = vec![];
+    SeededBag::new(4).extend(&mut queue, 10);
+    let mut battle = Battle::new(player(&queue), player(&queue));
+    for _ in 0..5 {
+        battle.advance().unwrap();
+    }
+    // the opponent plays a piece for every one of the player's
+    assert_eq!(battle.opponent.queue.len(), battle.player.queue.len());
+    assert_eq!(battle.opponent.game.board.cols, battle.player.game.board.cols);
+
+    battle.opponent.queue.clear();
+    assert_eq!(battle.advance().err(), Some(Side::Opponent));
+}
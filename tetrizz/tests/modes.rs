@@ -0,0 +1,91 @@
+use tetrizz::{
+    eval::{base::Eval, downstack::DownstackEval},
+    garbage::{Pattern, Scripted},
+    modes::{Mode, ModeSettings, play, points},
+    survival::survive
+};
+use utils::{
+    data::Spin,
+    game::{Game, PlacementInfo},
+    input::Finesse
+};
+
+fn info(lines_cleared: u8, spin: Spin, b2b_clear: bool, pc: bool) -> PlacementInfo {
+    PlacementInfo { lines_cleared, lines_received: 0, pc, b2b_clear, broke_surge: false, spin, outgoing_attack: 0 }
+}
+
+fn settings() -> ModeSettings {
+    ModeSettings { depth: 2, width: 50, max_pieces: 200, ..ModeSettings::default() }
+}
+
+#[test]
+fn scoring() {
+    // state after the placement: a first clear starts combo and b2b at 0
+    let first = Game { combo: 0, b2b: 0, ..Game::new_empty() };
+    assert_eq!(points(&first.state(), &info(4, Spin::None, true, false), 1), 800);
+    assert_eq!(points(&first.state(), &info(2, Spin::Full, true, false), 1), 1200);
+    assert_eq!(points(&first.state(), &info(1, Spin::Mini, true, false), 1), 200);
+    assert_eq!(points(&first.state(), &info(1, Spin::None, false, false), 3), 300);
+
+    let chained = Game { combo: 2, b2b: 1, ..Game::new_empty() };
+    assert_eq!(points(&chained.state(), &info(4, Spin::None, true, false), 1), 1200 + 100);
+    assert_eq!(points(&first.state(), &info(2, Spin::None, true, true), 1), 300 + 3500);
+    assert_eq!(points(&Game::new_empty().state(), &info(0, Spin::Full, false, false), 2), 800);
+}
+
+#[test]
+fn sprint() {
+    let result = play(&Mode::Sprint { lines: 4 }, &settings(), &Mode::SPRINT.eval(), 0);
+    assert!(!result.topped_out);
+    assert!(result.lines >= 4);
+    // 40 cells at least, so 10 pieces
+    assert!(result.pieces >= 10 && result.pieces < 30, "{result:?}");
+    assert!(result.keys >= result.pieces);
+    assert_eq!(result.finesse_faults, 0);
+}
+
+#[test]
+fn blitz_and_zen() {
+    let settings = ModeSettings { pps: 3.0, ..settings() };
+    let result = play(&Mode::Blitz { seconds: 5.0 }, &settings, &Mode::BLITZ.eval(), 1);
+    assert_eq!(result.pieces, 15);
+    assert_eq!(result.time(settings.pps), 5.0);
+
+    let settings = ModeSettings { max_pieces: 20, finesse: Finesse::Taps, ..settings };
+    let zen = play(&Mode::Zen, &settings, &Mode::Zen.eval(), 1);
+    assert_eq!(zen.pieces, 20);
+    // without das, pieces that go to the walls take extra presses
+    assert!(zen.finesse_faults > 0 && zen.keys_per_piece() > 3.0);
+}
+
+// sprint doesn't reward clearing early, and spins cost keys
+#[test]
+fn sprint_eval() {
+    let (game, sprint, zen) = (Game::new_empty(), Mode::SPRINT.eval(), Mode::Zen.eval());
+    let clear = info(1, Spin::None, false, false);
+    let spin = info(0, Spin::Mini, false, false);
+    assert!(zen.value(&game.state(), &clear) > zen.value(&game.state(), &info(0, Spin::None, false, false)));
+    assert_eq!(sprint.value(&game.state(), &clear), sprint.value(&game.state(), &info(0, Spin::None, false, false)));
+    assert!(sprint.value(&game.state(), &spin) < zen.value(&game.state(), &spin));
+}
+
+// survival is the same game survival::survive plays against a constant source
+#[test]
+fn survival_matches_survive() {
+    let settings = ModeSettings { max_pieces: 80, ..settings() };
+    let eval = DownstackEval::default();
+    let result = play(&Mode::Survival { apm: 40.0 }, &settings, &eval, 5);
+    let stats = survive(&mut Scripted::new(Pattern::Constant { apm: 40.0 }, settings.pps), &settings, &eval, 5);
+    assert_eq!((result.pieces, result.lines, result.attack), (stats.pieces, stats.lines, stats.attack));
+    assert!(stats.garbage_received > 0);
+}
+
+#[test]
+fn survival_tops_out() {
+    let result = play(&Mode::Survival { apm: 600.0 }, &settings(), &Mode::Survival { apm: 600.0 }.eval(), 2);
+    assert!(result.topped_out);
+    assert!(result.pieces < 200);
+    // stops on the same piece survive does
+    let stats = survive(&mut Scripted::new(Pattern::Constant { apm: 600.0 }, settings().pps), &settings(), &Mode::Survival { apm: 600.0 }.eval(), 2);
+    assert_eq!((result.pieces, result.topped_out), (stats.pieces, stats.topped_out));
+}
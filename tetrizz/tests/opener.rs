@@ -0,0 +1,173 @@
+use tetrizz::opener::{Book, Opener, OpenerError};
+use utils::{
+    data::{Piece, PieceLocation, Rotation, Spin},
+    game::Game
+};
+
+const TSD: &str = "
+    name: tsd
+    ...S......
+    L..SSOOZZ.
+    LtttSOOJZZ
+    LLtIIIIJJJ
+";
+
+fn queue(s: &str) -> Vec<Piece> {
+    s.chars().map(|c| Piece::from_letter(c).unwrap()).collect()
+}
+
+// plays the move like a client would, holding if it isn't the current piece
+fn play(game: &mut Game, queue: &mut Vec<Piece>, loc: &PieceLocation) -> u8 {
+    let current = queue.remove(0);
+    if loc.piece != current && game.hold.is_none() {
+        queue.remove(0);
+    }
+    game.advance(current, loc).lines_cleared
+}
+
+// follows the book from an empty board, returning the lines cleared by each placement
+fn follow(book: &Book, q: &str) -> Vec<(PieceLocation, u8)> {
+    let mut game = Game::new_empty();
+    let mut queue = queue(q);
+    let mut played = vec![];
+    while let Some(loc) = book.next_move(&game, &queue) {
+        let lines = play(&mut game, &mut queue, &loc);
+        played.push((loc, lines));
+    }
+    played
+}
+
+#[test]
+fn parses() {
+    let opener = Opener::parse(TSD).unwrap();
+    assert_eq!(opener.name, "tsd");
+    assert_eq!(opener.placements.len(), 7);
+    assert_eq!(opener.placements.iter().filter(|p| p.spin).count(), 1);
+
+    let book = Book::builtin();
+    // tsd, then tki, pco, mko and dt cannon in two stages each, all mirrored
+    assert_eq!(book.openers.len(), 18);
+    assert_eq!(book.openers[1].name, "tsd (mirror)");
+
+    assert!(matches!(Opener::parse("L..SSOOZZ"), Err(OpenerError::Width { line: 1, width: 9 })));
+    assert!(matches!(Opener::parse("L..SSOOZZ?"), Err(OpenerError::Cell { line: 1, cell: '?' })));
+    assert!(matches!(Opener::parse("IIIII....."), Err(OpenerError::Shape('I'))));
+    assert!(matches!(Opener::parse("size: 3"), Err(OpenerError::Header(_))));
+}
+
+const QUEUES: [&str; 4] = ["IOLJSZT", "TIOLJSZ", "LJITOSZ", "JTILOZS"];
+
+#[test]
+fn builds_tsd() {
+    let book = Book::parse(TSD).unwrap();
+    for q in QUEUES {
+        let played = follow(&book, &format!("{q}IOLJSZT"));
+        assert_eq!(played.len(), 7, "{q}: {played:?}");
+        let (last, lines) = played.last().unwrap();
+        assert_eq!((last.piece, last.spin, *lines), (Piece::T, Spin::Full, 2), "{q}");
+    }
+}
+
+// follows a builtin opener through all its stages, checking the lines it clears. then a queue it can't take leaves the
+// board to search after `diverged` placements
+fn builds(name: &str, bags: &str, placements: usize, clears: &[u8], divergent: &str, diverged: usize) {
+    let book = Book { openers: Book::builtin().openers.into_iter().filter(|o| o.name.starts_with(name)).collect() };
+    let played = follow(&book, bags);
+    assert_eq!(played.len(), placements, "{name}: {played:?}");
+    let lines: Vec<u8> = played.iter().map(|&(_, lines)| lines).filter(|&lines| lines > 0).collect();
+    assert_eq!(lines, clears, "{name}");
+    assert_eq!(played.last().unwrap().1, *clears.last().unwrap(), "{name}");
+
+    let played = follow(&book, divergent);
+    assert_eq!(played.len(), diverged, "{name}: {played:?}");
+}
+
+#[test]
+fn builds_tki() {
+    builds("tki", "IOLJSZTILOSJZTIOLJSZT", 14, &[2, 2], "IOLJSZTIOLJSZTIOLJSZT", 7);
+}
+
+#[test]
+fn builds_pco() {
+    // a perfect clear. `before: ZI` keeps the opener off bags with the i first
+    builds("pco", "OLJSZITILTOJSZIOLJSZT", 10, &[4], "IOLJSZTILTOJSZIOLJSZT", 0);
+}
+
+#[test]
+fn builds_mko() {
+    builds("mko", "IOLJSZTIOJSLZTIOLJSZT", 14, &[2, 2], "IOLJSZTIOLJSZTIOLJSZT", 7);
+}
+
+#[test]
+fn builds_dt_cannon() {
+    // a tsd then a tst
+    builds("dt cannon", "IOLJSZTIOLJSZTIOLJSZT", 14, &[2, 3], "IOLJSZTIOLJZTSIOLJSZT", 7);
+}
+
+#[test]
+fn mirror() {
+    let opener = Opener::parse(TSD).unwrap();
+    let mirrored = opener.mirrored();
+    for (p, m) in opener.placements.iter().zip(&mirrored.placements) {
+        let mut cells: Vec<(i8, i8)> = p.loc.blocks().iter().map(|&(x, y)| (9 - x, y)).collect();
+        let mut mirrored_cells = m.loc.blocks().to_vec();
+        cells.sort();
+        mirrored_cells.sort();
+        assert_eq!(cells, mirrored_cells);
+    }
+    let book = Book { openers: vec![mirrored] };
+    for q in QUEUES {
+        let q: String = q.chars().map(|c| match c { 'L' => 'J', 'J' => 'L', 'S' => 'Z', 'Z' => 'S', c => c }).collect();
+        let played = follow(&book, &format!("{q}IOLJSZT"));
+        assert_eq!(played.len(), 7, "{q}");
+        assert_eq!(played[6].1, 2);
+    }
+}
+
+#[test]
+fn diverges() {
+    let book = Book::parse(TSD).unwrap();
+    let mut game = Game::new_empty();
+    let mut q = queue("IOLJSZT");
+    // not part of the opener
+    let loc = PieceLocation { piece: Piece::I, x: 1, y: 0, rotation: Rotation::North, spin: Spin::None };
+    assert!(book.next_move(&game, &q).is_some());
+    play(&mut game, &mut q, &loc);
+    assert!(book.next_move(&game, &q).is_none());
+
+    // garbage
+    let mut game = Game::new_empty();
+    game.board.add_garbage(3, 1);
+    assert!(book.next_move(&game, &queue("IOLJSZT")).is_none());
+
+    // two pieces the opener can't use in a row
+    assert!(book.next_move(&Game::new_empty(), &queue("TTIOLJSZ")).is_none());
+}
+
+#[test]
+fn before() {
+    let book = Book::parse(&format!("before: TS\n{TSD}")).unwrap();
+    assert!(book.openers[0].plan(&Game::new_empty(), &queue("TIOLJSZ")).is_some());
+    assert!(book.openers[0].plan(&Game::new_empty(), &queue("SIOLJZT")).is_none());
+    // unknown pieces don't count against it
+    assert!(book.openers[0].plan(&Game::new_empty(), &queue("IOL")).is_some());
+}
+
+
+// openers longer than a bag are one entry per stage, the later ones starting from what the earlier ones leave
+#[test]
+fn stages() {
+    let book = Book::parse(&format!("{TSD}
+---
+name: tsd, second bag
+.........I
+.........I
+.OO#.....I
+#OO######I
+")).unwrap();
+    let played = follow(&book, "IOLJSZTIO");
+    assert_eq!(played.len(), 9, "{played:?}");
+    assert_eq!((played[6].0.piece, played[6].1), (Piece::T, 2));
+    // the second stage is only looked at once the tsd has cleared, and its line clear comes last
+    assert_eq!((played[7].0.piece, played[7].1, played[8].0.piece, played[8].1), (Piece::I, 0, Piece::O, 1));
+}
@@ -0,0 +1,102 @@
+use tetrizz::pc::{all_pcs, find_pcs, first_pc, pieces_needed, plausible};
+use utils::{
+    data::{Piece, PieceLocation},
+    game::Game
+};
+
+// plays the moves like a client would, holding when the piece isn't the current one. true if the last one is a pc
+fn replay(game: &Game, queue: &[Piece], moves: &[PieceLocation]) -> bool {
+    let mut game = game.clone();
+    let mut queue = queue.to_vec();
+    let mut pc = false;
+    for loc in moves {
+        let current = queue.remove(0);
+        if loc.piece != current && game.hold.is_none() {
+            queue.remove(0);
+        }
+        assert!(!game.board.collides(loc), "{loc:?}");
+        pc = game.advance(current, loc).pc;
+    }
+    pc
+}
+
+#[test]
+fn two_lines() {
+    let (game, queue) = Game::from_ascii("queue: IIOII").unwrap();
+    let pcs = find_pcs(&game, &queue, 2);
+    assert!(!pcs.is_empty());
+    for moves in &pcs {
+        assert_eq!(moves.len(), 5);
+        assert!(replay(&game, &queue, moves), "{moves:?}");
+    }
+    // no height other than 2 works with 5 pieces
+    assert_eq!(all_pcs(&game, &queue, 6).len(), pcs.len());
+    assert!(find_pcs(&game, &queue, 3).is_empty());
+}
+
+#[test]
+fn with_hold() {
+    // the s can't go anywhere, so the held i has to come out first
+    let (game, queue) = Game::from_ascii("
+        hold: I
+        queue: SI
+        ....######
+        ....######
+    ").unwrap();
+    assert_eq!(pieces_needed(&game.board, 2), Some(2));
+    let pcs = find_pcs(&game, &queue, 2);
+    assert!(!pcs.is_empty());
+    for moves in &pcs {
+        assert!(replay(&game, &queue, moves), "{moves:?}");
+        assert!(moves.iter().all(|m| m.piece != Piece::S));
+    }
+}
+
+#[test]
+fn four_lines() {
+    let (game, queue) = Game::from_ascii("
+        queue: IJLTSZOI
+        ####......
+        ####......
+        ####......
+        ####......
+    ").unwrap();
+    let pcs = find_pcs(&game, &queue, 4);
+    assert!(!pcs.is_empty());
+    for moves in &pcs {
+        assert_eq!(moves.len(), 6);
+        assert!(replay(&game, &queue, moves), "{moves:?}");
+    }
+    // nothing lower fits under the cells already there
+    let moves = first_pc(&game, &queue, 6).unwrap();
+    assert_eq!(moves.len(), 6);
+
+    // from an empty board with a bag and a half
+    let (game, queue) = Game::from_ascii("queue: TILJOSZTILJOSZ").unwrap();
+    let moves = first_pc(&game, &queue, 4).unwrap();
+    assert_eq!(moves.len(), 10);
+    assert!(replay(&game, &queue, &moves));
+}
+
+#[test]
+fn impossible() {
+    // 5 pieces for 2 lines, but s and z always leave something sticking out
+    let (game, queue) = Game::from_ascii("queue: SSZZS").unwrap();
+    assert!(plausible(&game, &queue, 2));
+    assert!(find_pcs(&game, &queue, 2).is_empty());
+
+    // an odd number of l and j with no t can't even out the columns
+    let (game, queue) = Game::from_ascii("queue: LOOOO").unwrap();
+    assert!(find_pcs(&game, &queue, 2).is_empty());
+
+    // too many cells for the height, or too few pieces
+    let (game, queue) = Game::from_ascii("
+        queue: IIII
+        ..........
+        #.........
+        ##########
+    ").unwrap();
+    assert_eq!(pieces_needed(&game.board, 2), None);
+    assert!(!plausible(&game, &queue, 3));
+    assert!(first_pc(&game, &queue, 6).is_none());
+}
@@ -0,0 +1,176 @@
+use tetrizz::{
+    eval::downstack::DownstackEval,
+    search::{Opponent, expected_attack, expected_hole, pieces, place, search_pv, search_pv_vs}
+};
+use utils::{
+    data::{Piece, PieceLocation},
+    game::{GameConfig, GarbageSpeculation, Game}
+};
+
+// plays the line like a client would, checking every placement uses the current piece or the one hold gives. returns
+// the lines cleared by each placement
+fn replay(game: &Game, queue: &[Piece], pv: &[PieceLocation]) -> Vec<u8> {
+    let mut game = game.clone();
+    let mut queue = queue.to_vec();
+    pv.iter().map(|loc| {
+        if queue.is_empty() {
+            // the held piece, after the queue ran out
+            assert_eq!(game.hold.take(), Some(loc.piece));
+            return game.advance(loc.piece, loc).lines_cleared;
+        }
+        let current = queue.remove(0);
+        let swap = game.hold.or(queue.first().copied());
+        assert!(loc.piece == current || Some(loc.piece) == swap, "{loc:?} with {current:?} and {swap:?}");
+        if loc.piece != current && game.hold.is_none() {
+            queue.remove(0);
+        }
+        assert!(!game.board.collides(loc), "{loc:?}");
+        game.advance(current, loc).lines_cleared
+    }).collect()
+}
+
+fn quad_ready() -> Opponent {
+    let (game, queue) = Game::from_ascii("
+        queue: III
+        .#########
+        .#########
+        .#########
+        .#########
+        #########.
+    ").unwrap();
+    Opponent { game, queue }
+}
+
+#[test]
+fn opponent_attack() {
+    let eval = DownstackEval::default();
+    let opponent = quad_ready();
+    // the quad, with whatever the attack table adds on top
+    let attack = expected_attack(&opponent, &eval, 1);
+    assert!(attack.len() == 1 && attack[0] >= 4, "{attack:?}");
+    // only as far as their queue goes
+    assert!(expected_attack(&opponent, &eval, 10).len() <= 3);
+    // with more pieces to look at the quad can come later, but it still comes
+    let attack = expected_attack(&opponent, &eval, 3);
+    assert_eq!(attack.iter().filter(|&&a| a >= 4).count(), 1, "{attack:?}");
+
+    let none = Opponent { queue: vec![], ..quad_ready() };
+    assert!(expected_attack(&none, &eval, 3).is_empty());
+}
+
+#[test]
+fn no_attack_changes_nothing() {
+    let eval = DownstackEval::default();
+    let (game, queue) = Game::from_ascii("queue: TSZLJ").unwrap();
+    let (quiet, _) = Game::from_ascii("").unwrap();
+    let opponent = Opponent { game: quiet, queue: queue.clone() };
+    let vs = search_pv_vs(&game, &queue, &eval, 4, 200, Some(&opponent));
+    assert_eq!(format!("{vs:?}"), format!("{:?}", search_pv(&game, &queue, &eval, 4, 200)));
+}
+
+#[test]
+fn hold_pieces() {
+    use Piece::*;
+    let queue = [T, S, Z];
+    assert_eq!(pieces(&queue, 0, None), Some((T, Some(S))));
+    assert_eq!(pieces(&queue, 0, Some(I)), Some((T, Some(I))));
+    // holding the same piece changes nothing
+    assert_eq!(pieces(&queue, 0, Some(T)), Some((T, None)));
+    assert_eq!(pieces(&queue, 2, None), Some((Z, None)));
+    // the held piece is the last one left
+    assert_eq!(pieces(&queue, 3, Some(I)), Some((I, None)));
+    assert_eq!(pieces(&queue, 3, None), None);
+}
+
+#[test]
+fn hold_cursor() {
+    let (game, queue) = Game::from_ascii("queue: TSZ").unwrap();
+    let pv = search_pv(&game, &queue, &DownstackEval::default(), 1, 200);
+    let mut s = pv[0].clone();
+    s.piece = Piece::S;
+
+    // holding with nothing held takes two pieces off the queue
+    let (held, _, cursor) = place(&game.state(), &queue, 0, &s, 0);
+    assert_eq!((held.hold, cursor), (Some(Piece::T), 2));
+    // swapping with the held piece only one
+    let swapped = Game { hold: Some(Piece::S), ..game.clone() };
+    let (swapped, _, cursor) = place(&swapped.state(), &queue, 0, &s, 0);
+    assert_eq!((swapped.hold, cursor), (Some(Piece::T), 1));
+    // the held piece played after the queue leaves nothing
+    let last = Game { hold: Some(Piece::S), ..game.clone() };
+    let (last, _, cursor) = place(&last.state(), &queue, 3, &s, 0);
+    assert_eq!((last.hold, cursor), (None, 3));
+    // none of which touches the game it started from
+    assert_eq!(game.hold, None);
+    assert_eq!(game.board.cols, [0; 10]);
+}
+
+#[test]
+fn hold_lines() {
+    // the i is needed for the quad but comes last, so getting it early means holding, and the piece after a hold is the
+    // one after the held one
+    let (game, queue) = Game::from_ascii("
+        queue: OSZI
+        .#########
+        .#########
+        .#########
+        .#########
+        #########.
+    ").unwrap();
+    let eval = DownstackEval::default();
+    for depth in 1..=4 {
+        let pv = search_pv(&game, &queue, &eval, depth, 500);
+        assert_eq!(pv.len(), depth);
+        replay(&game, &queue, &pv);
+    }
+    let pv = search_pv(&game, &queue, &eval, 4, 500);
+    assert!(replay(&game, &queue, &pv).contains(&4), "{pv:?}");
+
+    // an o held from the start can go anywhere in the line
+    let (mut held, queue) = Game::from_ascii("queue: TSZ").unwrap();
+    held.hold = Some(Piece::O);
+    let pv = search_pv(&held, &queue, &eval, 3, 200);
+    assert_eq!(replay(&held, &queue, &pv).len(), 3);
+}
+
+#[test]
+fn garbage_speculation() {
+    let (mut game, queue) = Game::from_ascii("
+        queue: TSZLJO
+        ....######
+        ###.######
+    ").unwrap();
+    game.incoming_garbage = 6;
+    let eval = DownstackEval::default();
+    for speculation in [GarbageSpeculation::Expected, GarbageSpeculation::Sampled(4), GarbageSpeculation::Sampled(10)] {
+        game.config.speculation = speculation;
+        let pv = search_pv(&game, &queue, &eval, 5, 300);
+        assert_eq!(pv.len(), 5);
+        for _ in 0..3 {
+            assert_eq!(format!("{:?}", search_pv(&game, &queue, &eval, 5, 300)), format!("{pv:?}"), "{speculation:?}");
+        }
+    }
+
+    // the hole under the stack is where more garbage is expected
+    assert_eq!(expected_hole(&game.board), 3);
+    assert_eq!(expected_hole(&Game::new_empty().board), 0);
+
+    // on a symmetric board the holes spread across it average out, and the sampled child carries on from the same
+    // expected hole, so both pick the same line
+    let (mut game, queue) = Game::from_ascii("
+        queue: TSZLJO
+        ##......##
+        ###....###
+    ").unwrap();
+    game.incoming_garbage = 4;
+    let pvs: Vec<String> = [GarbageSpeculation::Expected, GarbageSpeculation::Sampled(10)].into_iter().map(|speculation| {
+        game.config.speculation = speculation;
+        format!("{:?}", search_pv(&game, &queue, &eval, 4, 300))
+    }).collect();
+    assert_eq!(pvs[0], pvs[1]);
+
+    let config: GameConfig = serde_json::from_str(r#"{"speculation": {"Sampled": 4}}"#).unwrap();
+    assert_eq!(config.speculation, GarbageSpeculation::Sampled(4));
+    let config: GameConfig = serde_json::from_str("{}").unwrap();
+    assert_eq!(config.speculation, GarbageSpeculation::Expected);
+}
@@ -0,0 +1,95 @@
+use std::{sync::Arc, thread};
+
+use serde_json::{Value, json};
+use tetrizz::{
+    eval::base::MinimalEval,
+    search::{search, search_pv},
+    server::Rooms
+};
+use utils::{data::Piece, game::Game};
+
+fn eval() -> MinimalEval {
+    MinimalEval { values: [-333.5903388433292, -257.50971325030974, -51.15233297009883, -306.7533752401169, -185.4127319664896, -107.0342999530273, -369.0213829655287, 96.63015564572557, -476.6250155906852, -58.72828521759004, 1068.1212353788042, -1016.9857268318688, -457.5993254187147, 2280.857332747434, 0.0] }
+}
+
+fn send(rooms: &Rooms<MinimalEval>, room: &str, request: Value) -> Value {
+    serde_json::from_str(&rooms.handle_json(room, &request.to_string())).unwrap()
+}
+
+fn setup(rooms: &Rooms<MinimalEval>, room: &str, queue: &[&str]) {
+    let state = json!({ "type": "state", "game": Game::new_empty(), "queue": queue });
+    assert_eq!(send(rooms, room, state)["type"], "ok");
+    assert_eq!(send(rooms, room, json!({ "type": "config", "depth": 3, "width": 50 }))["type"], "ok");
+}
+
+#[test]
+fn pv_starts_with_the_best_move() {
+    let queue = vec![Piece::T, Piece::I, Piece::O, Piece::L];
+    let game = Game::new_empty();
+    let pv = search_pv(&game, &queue, &eval(), 4, 100);
+    assert_eq!(pv.len(), 4);
+    assert_eq!(format!("{:?}", pv[0]), format!("{:?}", search(&game, &queue, &eval(), 4, 100).unwrap()));
+}
+
+#[test]
+fn suggest_and_pv() {
+    let rooms = Rooms::new(eval());
+    setup(&rooms, "a", &["T", "I", "O", "L"]);
+
+    let suggestion = send(&rooms, "a", json!({ "type": "suggest" }));
+    assert_eq!(suggestion["type"], "suggestion");
+    let keys = suggestion["keys"].as_array().unwrap();
+    assert_eq!(keys.last().unwrap(), "Harddrop");
+    assert_eq!(keys[0] == "Hold", suggestion["location"]["piece"] != "T");
+
+    let pv = send(&rooms, "a", json!({ "type": "pv" }));
+    assert_eq!(pv["moves"].as_array().unwrap().len(), 3);
+    assert_eq!(pv["moves"][0], suggestion["location"]);
+}
+
+#[test]
+fn state_and_config() {
+    let rooms = Rooms::new(eval());
+    let state = send(&rooms, "a", json!({ "type": "get_state" }));
+    assert_eq!(state["queue"], json!([]));
+    assert_eq!(state["config"]["depth"], 6);
+    assert_eq!(send(&rooms, "a", json!({ "type": "suggest" }))["kind"], "empty_queue");
+
+    setup(&rooms, "a", &["S", "Z"]);
+    let state = send(&rooms, "a", json!({ "type": "get_state" }));
+    assert_eq!(state["queue"], json!(["S", "Z"]));
+    assert_eq!(state["config"], json!({ "depth": 3, "width": 50, "human": false }));
+}
+
+#[test]
+fn errors() {
+    let rooms = Rooms::new(eval());
+    assert_eq!(send(&rooms, "a", json!({ "type": "dance" }))["kind"], "invalid_json");
+    assert_eq!(serde_json::from_str::<Value>(&rooms.handle_json("a", "{")).unwrap()["type"], "error");
+    assert_eq!(send(&rooms, "a", json!({ "type": "pv" }))["kind"], "empty_queue");
+    // the room is still usable afterwards
+    assert_eq!(send(&rooms, "a", json!({ "type": "get_state" }))["type"], "state");
+}
+
+#[test]
+fn rooms_are_separate() {
+    let rooms = Arc::new(Rooms::new(eval()));
+    setup(&rooms, "a", &["I"]);
+    setup(&rooms, "b", &["O", "T"]);
+    assert!(rooms.remove("b"));
+    assert!(!rooms.remove("b"));
+    assert_eq!(send(&rooms, "b", json!({ "type": "get_state" }))["queue"], json!([]));
+    assert_eq!(send(&rooms, "a", json!({ "type": "get_state" }))["queue"], json!(["I"]));
+
+    let handles: Vec<_> = ["T", "I", "O", "L"].into_iter().map(|piece| {
+        let rooms = rooms.clone();
+        thread::spawn(move || {
+            setup(&rooms, piece, &[piece, "S", "Z"]);
+            send(&rooms, piece, json!({ "type": "suggest" }))
+        })
+    }).collect();
+    for (piece, handle) in ["T", "I", "O", "L"].into_iter().zip(handles) {
+        let suggestion = handle.join().unwrap();
+        assert!(suggestion["location"]["piece"] == piece || suggestion["keys"][0] == "Hold");
+    }
+}
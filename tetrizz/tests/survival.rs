@@ -0,0 +1,37 @@
+use tetrizz::{
+    eval::downstack::DownstackEval,
+    garbage::{Pattern, Scripted},
+    modes::ModeSettings,
+    survival::{SurvivalStats, survive}
+};
+
+#[test]
+fn stats() {
+    let settings = ModeSettings { depth: 2, width: 50, max_pieces: 120, ..ModeSettings::default() };
+    let mut source = Scripted::new(Pattern::Constant { apm: 30.0 }, settings.pps);
+    let stats = survive(&mut source, &settings, &DownstackEval::default(), 3);
+    assert!(!stats.topped_out, "{stats:?}");
+    assert_eq!(stats.pieces, 120);
+    assert!(stats.garbage_received > 0 && stats.garbage_cleared > 0);
+    assert!(stats.garbage_cleared <= stats.garbage_received && stats.garbage_cleared <= stats.lines);
+    assert!(stats.b2b_chains.iter().sum::<u32>() <= stats.lines);
+    assert_eq!(stats.apm(settings.pps), stats.app() * 120.0);
+    assert_eq!(stats.time(settings.pps), 60.0);
+
+    let columns = SurvivalStats::CSV_HEADER.split(',').count();
+    assert_eq!(stats.csv_row(settings.pps).split(',').count(), columns);
+
+    // search doesn't roll garbage holes, so the same seed plays the same game
+    let mut source = Scripted::new(Pattern::Constant { apm: 30.0 }, settings.pps);
+    let again = survive(&mut source, &settings, &DownstackEval::default(), 3);
+    assert_eq!(again.csv_row(settings.pps), stats.csv_row(settings.pps));
+}
+
+#[test]
+fn tops_out() {
+    let settings = ModeSettings { depth: 2, width: 50, max_pieces: 300, ..ModeSettings::default() };
+    let mut source = Scripted::new(Pattern::Burst { lines: 12, every: 2.0 }, settings.pps);
+    let stats = survive(&mut source, &settings, &DownstackEval::default(), 0);
+    assert!(stats.topped_out);
+    assert!(stats.pieces < 300);
+}
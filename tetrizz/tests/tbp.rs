@@ -0,0 +1,105 @@
+use tetrizz::{
+    error::BotError,
+    eval::base::MinimalEval,
+    tbp::{Bot, BotMessage, FrontendMessage}
+};
+
+fn bot() -> Bot<MinimalEval> {
+    let eval = MinimalEval { values: [-333.5903388433292, -257.50971325030974, -51.15233297009883, -306.7533752401169, -185.4127319664896, -107.0342999530273, -369.0213829655287, 96.63015564572557, -476.6250155906852, -58.72828521759004, 1068.1212353788042, -1016.9857268318688, -457.5993254187147, 2280.857332747434, 0.0] };
+    Bot::new(eval, 3, 50)
+}
+
+fn msg(json: &str) -> FrontendMessage {
+    serde_json::from_str(json).unwrap()
+}
+
+fn start(hold: &str, queue: &str) -> FrontendMessage {
+    let board = vec![vec![None::<char>; 10]; 40];
+    msg(&format!(
+        r#"{{"type":"start","hold":{hold},"queue":{queue},"combo":0,"back_to_back":false,"board":{}}}"#,
+        serde_json::to_string(&board).unwrap()
+    ))
+}
+
+fn suggest(bot: &mut Bot<MinimalEval>) -> serde_json::Value {
+    let reply = bot.handle(msg(r#"{"type":"suggest"}"#)).unwrap().unwrap();
+    let BotMessage::Suggestion { moves } = &reply else { panic!("{reply:?}") };
+    serde_json::to_value(moves).unwrap()
+}
+
+fn play(bot: &mut Bot<MinimalEval>, piece: &str, orientation: &str, x: i8, y: i8) -> Result<Option<BotMessage>, BotError> {
+    bot.handle(msg(&format!(
+        r#"{{"type":"play","move":{{"location":{{"type":"{piece}","orientation":"{orientation}","x":{x},"y":{y}}},"spin":"none"}}}}"#
+    )))
+}
+
+#[test]
+fn info_and_rules() {
+    let info = serde_json::to_value(Bot::<MinimalEval>::info()).unwrap();
+    assert_eq!(info["type"], "info");
+    assert_eq!(info["name"], "tetrizz");
+    let reply = bot().handle(msg(r#"{"type":"rules","randomizer":"seven_bag"}"#)).unwrap();
+    assert_eq!(serde_json::to_value(reply).unwrap()["type"], "ready");
+    assert!(matches!(msg(r#"{"type":"something_new","x":1}"#), FrontendMessage::Unknown));
+}
+
+#[test]
+fn suggest_and_play() {
+    let mut bot = bot();
+    bot.handle(start("null", r#"["T","I","O","L"]"#)).unwrap();
+    let moves = suggest(&mut bot);
+    assert_eq!(moves.as_array().unwrap().len(), 1);
+    let location = &moves[0]["location"];
+    assert!(["T", "I"].contains(&location["type"].as_str().unwrap()));
+    assert!(["north", "east", "south", "west"].contains(&location["orientation"].as_str().unwrap()));
+
+    bot.handle(serde_json::from_value(serde_json::json!({ "type": "play", "move": moves[0] })).unwrap()).unwrap();
+    bot.handle(msg(r#"{"type":"new_piece","piece":"Z"}"#)).unwrap();
+    assert_eq!(suggest(&mut bot).as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn play_with_hold() {
+    let mut bot = bot();
+    bot.handle(start("null", r#"["T","I","O","J"]"#)).unwrap();
+    // hold is empty, so playing the I holds the T
+    play(&mut bot, "I", "north", 4, 0).unwrap();
+    // now O is current and T is held
+    play(&mut bot, "T", "north", 4, 1).unwrap();
+    assert!(play(&mut bot, "T", "north", 4, 3).is_err());
+    play(&mut bot, "O", "north", 0, 0).unwrap();
+    assert!(suggest(&mut bot).as_array().unwrap().is_empty());
+}
+
+#[test]
+fn rejects_bad_plays() {
+    let mut bot = bot();
+    assert!(matches!(play(&mut bot, "T", "north", 4, 0), Err(BotError::NoGame)));
+    bot.handle(start(r#""S""#, r#"["T","I"]"#)).unwrap();
+    assert!(matches!(play(&mut bot, "I", "north", 4, 0), Err(BotError::IllegalMove(_))));
+    assert!(matches!(play(&mut bot, "T", "north", 0, 0), Err(BotError::IllegalMove(_))));
+    assert!(matches!(play(&mut bot, "T", "south", 4, 0), Err(BotError::IllegalMove(_))));
+    play(&mut bot, "S", "north", 4, 0).unwrap();
+    bot.handle(msg(r#"{"type":"stop"}"#)).unwrap();
+    assert!(matches!(bot.handle(msg(r#"{"type":"suggest"}"#)), Err(BotError::NoGame)));
+    // which still gets answered, with no moves
+    let reply = msg(r#"{"type":"suggest"}"#).error_reply().unwrap();
+    assert_eq!(serde_json::to_value(reply).unwrap(), serde_json::json!({ "type": "suggestion", "moves": [] }));
+    assert!(msg(r#"{"type":"play","move":{"location":{"type":"T","orientation":"north","x":4,"y":0}}}"#).error_reply().is_none());
+}
+
+#[test]
+fn rejects_bad_boards() {
+    let mut bot = bot();
+    let mut board = vec![vec![None::<char>; 10]; 40];
+    board[0][0] = Some('X');
+    let start = |board: &Vec<Vec<Option<char>>>| msg(&serde_json::json!({
+        "type": "start", "hold": null, "queue": ["T"], "combo": 0, "back_to_back": false, "board": board
+    }).to_string());
+    assert!(matches!(bot.handle(start(&board)), Err(BotError::Piece(_))));
+    board[0][0] = Some('G');
+    board[1].pop();
+    assert!(matches!(bot.handle(start(&board)), Err(BotError::Board(_))));
+    board[1].push(Some('L'));
+    bot.handle(start(&board)).unwrap();
+}
@@ -0,0 +1,133 @@
+use bitboard_traits::BitboardTrait;
+
+use crate::{
+    data::{Board, Piece},
+    game::Game
+};
+
+// plain text positions for tests and bug reports. rows are written top to bottom, 10 cells each:
+// `.` (or `_`) is empty, `#`, `X`, `G` or a piece letter is filled. the bottom row is y = 0.
+// a game may start with `key: value` header lines: hold, queue, b2b, combo and garbage (incoming lines).
+//
+//     hold: T
+//     queue: IOSZ
+//     ..........
+//     ##...#####
+//     ###.######
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AsciiError {
+    Width { line: usize, width: usize },
+    Cell { line: usize, cell: char },
+    Piece(char),
+    Header(String)
+}
+
+impl std::fmt::Display for AsciiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AsciiError::Width { line, width } => write!(f, "line {line} has {width} cells, expected 10"),
+            AsciiError::Cell { line, cell } => write!(f, "line {line} has an invalid cell {cell:?}"),
+            AsciiError::Piece(c) => write!(f, "invalid piece {c:?}"),
+            AsciiError::Header(h) => write!(f, "invalid header {h:?}")
+        }
+    }
+}
+
+impl std::error::Error for AsciiError {}
+
+impl Board {
+    pub fn from_ascii(s: &str) -> Result<Self, AsciiError> {
+        let rows: Vec<(usize, &str)> = s.lines()
+            .enumerate()
+            .map(|(i, l)| (i + 1, l.trim()))
+            .filter(|(_, l)| !l.is_empty())
+            .collect();
+        let mut board = Board::new();
+        for (y, (line, row)) in rows.iter().rev().enumerate() {
+            let cells: Vec<char> = row.chars().filter(|c| !c.is_whitespace()).collect();
+            if cells.len() != 10 {
+                return Err(AsciiError::Width { line: *line, width: cells.len() });
+            }
+            for (x, &cell) in cells.iter().enumerate() {
+                match cell {
+                    '.' | '_' => {}
+                    '#' | 'X' | 'G' | 'I' | 'O' | 'T' | 'L' | 'J' | 'S' | 'Z' => board.cols[x] |= 1 << y,
+                    _ => return Err(AsciiError::Cell { line: *line, cell })
+                }
+            }
+        }
+        Ok(board)
+    }
+
+    // every row up to the highest filled cell, so an empty board is an empty string
+    pub fn to_ascii(&self) -> String {
+        let mut rows = vec![];
+        for y in (0..self.max_height()).rev() {
+            rows.push((0..10).map(|x| if self.cols[x] & (1 << y) > 0 { '#' } else { '.' }).collect::<String>());
+        }
+        rows.join("\n")
+    }
+}
+
+impl Game {
+    // returns the game along with its queue (empty if there is no queue header)
+    pub fn from_ascii(s: &str) -> Result<(Self, Vec<Piece>), AsciiError> {
+        let mut game = Game::new_empty();
+        let mut queue = vec![];
+        let mut board_start = 0;
+
+        for (i, line) in s.lines().enumerate() {
+            let Some((key, value)) = line.split_once(':') else {
+                if !line.trim().is_empty() {
+                    break;
+                }
+                board_start = i + 1;
+                continue;
+            };
+            let value = value.trim();
+            let header_err = || AsciiError::Header(line.trim().to_string());
+            match key.trim().to_ascii_lowercase().as_str() {
+                "hold" => game.hold = value.chars().next().map(piece_from_ascii).transpose()?,
+                "queue" => queue = value.chars().filter(|c| !c.is_whitespace()).map(piece_from_ascii).collect::<Result<_, _>>()?,
+                "b2b" => game.b2b = value.parse().map_err(|_| header_err())?,
+                "combo" => game.combo = value.parse().map_err(|_| header_err())?,
+                "garbage" => game.incoming_garbage = value.parse().map_err(|_| header_err())?,
+                _ => return Err(header_err())
+            }
+            board_start = i + 1;
+        }
+
+        let board_text: Vec<&str> = s.lines().skip(board_start).collect();
+        game.board = Board::from_ascii(&board_text.join("\n")).map_err(|e| match e {
+            AsciiError::Width { line, width } => AsciiError::Width { line: line + board_start, width },
+            AsciiError::Cell { line, cell } => AsciiError::Cell { line: line + board_start, cell },
+            e => e
+        })?;
+        Ok((game, queue))
+    }
+
+    pub fn to_ascii(&self, queue: &[Piece]) -> String {
+        let mut out = vec![];
+        if let Some(h) = self.hold {
+            out.push(format!("hold: {h:?}"));
+        }
+        if !queue.is_empty() {
+            out.push(format!("queue: {}", queue.iter().map(|p| format!("{p:?}")).collect::<String>()));
+        }
+        out.push(format!("b2b: {}", self.b2b));
+        out.push(format!("combo: {}", self.combo));
+        if self.incoming_garbage > 0 {
+            out.push(format!("garbage: {}", self.incoming_garbage));
+        }
+        let board = self.board.to_ascii();
+        if !board.is_empty() {
+            out.push(board);
+        }
+        out.join("\n")
+    }
+}
+
+fn piece_from_ascii(c: char) -> Result<Piece, AsciiError> {
+    Piece::from_letter(c).ok_or(AsciiError::Piece(c))
+}
@@ -0,0 +1,47 @@
+use crate::{
+    data::Board,
+    queue::TetrioRng
+};
+
+// cheese garbage for downstacking practice: every line has a single hole. messiness is the chance that a line's hole
+// moves away from the previous line's column, like tetr.io's garbage messiness. 1.0 is classic cheese, where no two
+// lines in a row share a hole; 0.0 is one straight well
+#[derive(Debug, Clone)]
+pub struct Cheese {
+    pub messiness: f64,
+    rng: TetrioRng,
+    hole: Option<usize>
+}
+
+impl Cheese {
+    pub fn new(seed: u64, messiness: f64) -> Self {
+        Self { messiness, rng: TetrioRng::new(seed), hole: None }
+    }
+
+    pub fn next_hole(&mut self) -> usize {
+        let hole = match self.hole {
+            Some(h) if self.rng.next_float() >= self.messiness => h,
+            Some(h) => {
+                let c = (self.rng.next_float() * 9.0) as usize;
+                if c >= h { c + 1 } else { c }
+            }
+            None => (self.rng.next_float() * 10.0) as usize
+        };
+        self.hole = Some(hole);
+        hole
+    }
+
+    // pushes lines in from the bottom, like received garbage
+    pub fn add_lines(&mut self, board: &mut Board, lines: usize) {
+        for _ in 0..lines {
+            let hole = self.next_hole();
+            board.add_garbage(hole, 1);
+        }
+    }
+
+    pub fn board(&mut self, lines: usize) -> Board {
+        let mut board = Board { cols: [0; 10] };
+        self.add_lines(&mut board, lines);
+        board
+    }
+}
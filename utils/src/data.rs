@@ -1,31 +1,31 @@
 use bitboard_traits::BitboardTrait;
 use bitboard_derive::Bitboard;
-use serde::Deserialize;
+use serde::{Serialize, Deserialize};
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Piece {
     I, O, T, L, J, S, Z
 }
 
 #[repr(u8)]
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Rotation {
     North, East, South, West
 }
 
 pub const ROT: [Rotation; 4] = [Rotation::North, Rotation::East, Rotation::South, Rotation::West];
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Spin {
     None, Full, Mini
 }
 
-#[derive(Bitboard, Debug, Clone, Deserialize)]
+#[derive(Bitboard, Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct Board {
     pub cols: [u64; 10]
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PieceLocation {
     pub piece: Piece,
     pub x: i8,
@@ -94,20 +94,45 @@ impl Piece {
         }
     }
 
-    pub const fn from_char(p: char) -> Self {
+    pub const fn from_char(p: char) -> Result<Self, PieceError> {
         match p {
-            'i' => Piece::I,
-            'o' => Piece::O,
-            'j' => Piece::J,
-            't' => Piece::T,
-            'l' => Piece::L,
-            's' => Piece::S,
-            'z' => Piece::Z,
-            _ => panic!("wtf is this piece")
+            'i' => Ok(Piece::I),
+            'o' => Ok(Piece::O),
+            'j' => Ok(Piece::J),
+            't' => Ok(Piece::T),
+            'l' => Ok(Piece::L),
+            's' => Ok(Piece::S),
+            'z' => Ok(Piece::Z),
+            _ => Err(PieceError(p))
+        }
+    }
+
+    // like from_char, but case insensitive
+    pub const fn from_letter(p: char) -> Option<Self> {
+        match p.to_ascii_uppercase() {
+            'I' => Some(Piece::I),
+            'O' => Some(Piece::O),
+            'J' => Some(Piece::J),
+            'T' => Some(Piece::T),
+            'L' => Some(Piece::L),
+            'S' => Some(Piece::S),
+            'Z' => Some(Piece::Z),
+            _ => None
         }
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PieceError(pub char);
+
+impl std::fmt::Display for PieceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid piece {:?}", self.0)
+    }
+}
+
+impl std::error::Error for PieceError {}
+
 macro_rules! lutify {
     (($e:expr) for $v:ident in [$($val:expr),*]) => {
         [$({
@@ -150,6 +175,85 @@ impl PieceLocation {
     }
 }
 
+// other position conventions. all of them use the same cells and rotations as ours, and y pointing up; only where the
+// position sits on the piece differs:
+// - tetr.io: the top left cell of the piece's srs bounding box (4x4 for I, 2x2 for O, 3x3 for the rest)
+// - srs: the cell the piece rotates around without kicks. that is the box center for 3x3 pieces; I and O rotate around
+//   the point in the middle of their box, so the cell above and left of that point is used
+// - fumen: the same as ours, except for some rotations of O, I, S and Z
+impl PieceLocation {
+    pub const fn to_tetrio(&self) -> (i8, i8) {
+        let (dx, dy) = box_offset(self.piece, self.rotation);
+        (self.x - dx, self.y - dy)
+    }
+
+    pub const fn from_tetrio(piece: Piece, rotation: Rotation, x: i8, y: i8) -> Self {
+        let (dx, dy) = box_offset(piece, rotation);
+        PieceLocation { piece, x: x + dx, y: y + dy, rotation, spin: Spin::None }
+    }
+
+    pub const fn to_srs(&self) -> (i8, i8) {
+        let (x, y) = self.to_tetrio();
+        let (dx, dy) = srs_pivot(self.piece);
+        (x + dx, y + dy)
+    }
+
+    pub const fn from_srs(piece: Piece, rotation: Rotation, x: i8, y: i8) -> Self {
+        let (dx, dy) = srs_pivot(piece);
+        Self::from_tetrio(piece, rotation, x - dx, y - dy)
+    }
+
+    pub const fn to_fumen(&self) -> (i8, i8) {
+        let (dx, dy) = fumen_offset(self.piece, self.rotation);
+        (self.x + dx, self.y + dy)
+    }
+
+    pub const fn from_fumen(piece: Piece, rotation: Rotation, x: i8, y: i8) -> Self {
+        let (dx, dy) = fumen_offset(piece, rotation);
+        PieceLocation { piece, x: x - dx, y: y - dy, rotation, spin: Spin::None }
+    }
+}
+
+// where a piece's position sits relative to the top left cell of its srs bounding box. everything but I rotates around a
+// fixed cell of its box; I rotates around its center block, which moves around the box
+pub const fn box_offset(piece: Piece, rotation: Rotation) -> (i8, i8) {
+    match (piece, rotation) {
+        (Piece::I, Rotation::North) => (1, -1),
+        (Piece::I, Rotation::East) => (2, -1),
+        (Piece::I, Rotation::South) => (2, -2),
+        (Piece::I, Rotation::West) => (1, -2),
+        (Piece::O, Rotation::North) => (0, -1),
+        (Piece::O, Rotation::East) => (0, 0),
+        (Piece::O, Rotation::South) => (1, 0),
+        (Piece::O, Rotation::West) => (1, -1),
+        _ => (1, -1)
+    }
+}
+
+// the srs rotation cell, from the top left of the box
+const fn srs_pivot(piece: Piece) -> (i8, i8) {
+    match piece {
+        Piece::O => (0, 0),
+        _ => (1, -1)
+    }
+}
+
+// fumen's piece shapes match ours, but the stored position is offset for some rotations of O, I, S and Z
+const fn fumen_offset(piece: Piece, rotation: Rotation) -> (i8, i8) {
+    match (piece, rotation) {
+        (Piece::O, Rotation::West) => (-1, 1),
+        (Piece::O, Rotation::South) => (-1, 0),
+        (Piece::O, Rotation::North) => (0, 1),
+        (Piece::I, Rotation::South) => (-1, 0),
+        (Piece::I, Rotation::West) => (0, 1),
+        (Piece::S, Rotation::North) => (0, 1),
+        (Piece::S, Rotation::East) => (1, 0),
+        (Piece::Z, Rotation::North) => (0, 1),
+        (Piece::Z, Rotation::West) => (-1, 0),
+        _ => (0, 0)
+    }
+}
+
 impl Board {
     #[inline(always)]
     pub fn add_garbage(&mut self, garb_col: usize, lines: u16) {
@@ -186,6 +290,12 @@ impl Board {
         false
     }
 
+    /// Like `obstructed`, but cells outside the walls or below the floor also count as collisions.
+    #[inline(always)]
+    pub fn collides(&self, loc: &PieceLocation) -> bool {
+        loc.blocks().iter().any(|&(x, y)| !(0..10).contains(&x) || y < 0 || self.cols[x as usize] & (1 << y) > 0)
+    }
+
     #[inline(always)]
     pub fn distance_to_ground(&self, loc: &PieceLocation) -> i8 {
         loc.blocks().iter()
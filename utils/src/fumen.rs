@@ -0,0 +1,443 @@
+use bitboard_traits::BitboardTrait;
+
+use crate::{
+    data::{Board, Piece, PieceLocation, Rotation},
+    game::Game
+};
+
+// fumen v115 (https://fumen.zui.jp). the field is 23 rows of 10 plus one garbage row under the floor,
+// stored top row first. pages only store the difference to the previous page's field (after its piece locked),
+// run length encoded. every number is written as little endian base 64 digits.
+// board cells don't keep colors, so filled cells are written as gray unless the previous page already had a color there.
+
+const ENCODE_TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const COMMENT_TABLE: &[u8; 95] = b" !\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~";
+
+const FIELD_TOP: i32 = 23;
+const FIELD_BLOCKS: usize = 240;
+const GRAY: u8 = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FumenError {
+    Version,
+    Character(char),
+    Truncated,
+    Corrupt
+}
+
+impl std::fmt::Display for FumenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FumenError::Version => write!(f, "not a v115 fumen"),
+            FumenError::Character(c) => write!(f, "invalid character {c:?} in fumen data"),
+            FumenError::Truncated => write!(f, "fumen data ended early"),
+            FumenError::Corrupt => write!(f, "fumen data is corrupt")
+        }
+    }
+}
+
+impl std::error::Error for FumenError {}
+
+#[derive(Debug, Clone)]
+pub struct FumenPage {
+    // the field shown on this page, before piece is placed
+    pub board: Board,
+    pub piece: Option<PieceLocation>,
+    pub comment: String,
+    // whether piece gets placed (and lines cleared) to produce the next page's field
+    pub lock: bool
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Fumen {
+    pub pages: Vec<FumenPage>
+}
+
+impl Fumen {
+    // one page per placement, starting from board. hold and queue go into a quiz comment on the first page
+    pub fn from_placements(board: &Board, placements: &[PieceLocation], hold: Option<Piece>, queue: &[Piece]) -> Self {
+        let mut pages = vec![];
+        let mut board = *board;
+        for (i, loc) in placements.iter().enumerate() {
+            pages.push(FumenPage {
+                board,
+                piece: Some(loc.clone()),
+                comment: if i == 0 { quiz_comment(hold, queue) } else { String::new() },
+                lock: true
+            });
+            board.put_piece(loc);
+            board.remove_lines();
+        }
+        if pages.is_empty() {
+            pages.push(FumenPage { board, piece: None, comment: quiz_comment(hold, queue), lock: true });
+        }
+        Self { pages }
+    }
+
+    pub fn placements(&self) -> Vec<PieceLocation> {
+        self.pages.iter().filter_map(|p| p.piece.clone()).collect()
+    }
+
+    // the first page as a game, with hold taken from its quiz comment if there is one
+    pub fn game(&self) -> Game {
+        let mut game = Game::new_empty();
+        if let Some(page) = self.pages.first() {
+            game.board = page.board;
+            game.hold = page.queue().and_then(|(h, _)| h);
+        }
+        game
+    }
+
+    pub fn decode(s: &str) -> Result<Self, FumenError> {
+        let start = s.find("115@").ok_or(FumenError::Version)?;
+        let data: Vec<u8> = s[start + 4..].bytes().filter(|&c| c != b'?' && !c.is_ascii_whitespace()).collect();
+        let mut reader = Reader { data: &data, pos: 0 };
+
+        let mut pages = vec![];
+        let mut prev_field = [0u8; FIELD_BLOCKS];
+        let mut prev_comment = String::new();
+        let mut repeat: Option<u64> = None;
+
+        while !reader.is_empty() {
+            let mut field = prev_field;
+            match repeat {
+                Some(n) if n > 0 => repeat = Some(n - 1),
+                _ => {
+                    let mut changed = false;
+                    let mut idx = 0;
+                    while idx < FIELD_BLOCKS {
+                        let v = reader.poll(2)?;
+                        let diff = (v / FIELD_BLOCKS as u64) as i32;
+                        let count = (v % FIELD_BLOCKS as u64) as usize + 1;
+                        changed |= !(diff == 8 && count == FIELD_BLOCKS);
+                        if idx + count > FIELD_BLOCKS || diff > 16 {
+                            return Err(FumenError::Corrupt);
+                        }
+                        for cell in &mut field[idx..idx + count] {
+                            *cell = (*cell as i32 + diff - 8).clamp(0, GRAY as i32) as u8;
+                        }
+                        idx += count;
+                    }
+                    repeat = if changed { None } else { Some(reader.poll(1)?) };
+                }
+            }
+
+            let mut v = reader.poll(3)?;
+            let kind = v % 8;
+            v /= 8;
+            let rotation = [Rotation::South, Rotation::East, Rotation::North, Rotation::West][(v % 4) as usize];
+            v /= 4;
+            let pos = (v % FIELD_BLOCKS as u64) as i32;
+            v /= FIELD_BLOCKS as u64;
+            let rise = v & 1 != 0;
+            let mirror = v & 2 != 0;
+            let comment_flag = v & 8 != 0;
+            let lock = v & 16 == 0;
+
+            let piece = match kind {
+                0 => None,
+                k => {
+                    let piece = piece_from_fumen(k as u8).ok_or(FumenError::Corrupt)?;
+                    Some(PieceLocation::from_fumen(piece, rotation, (pos % 10) as i8, (FIELD_TOP - pos / 10 - 1) as i8))
+                }
+            };
+
+            let comment = if comment_flag {
+                let len = reader.poll(2)? as usize;
+                let mut escaped = String::with_capacity(len);
+                while escaped.len() < len {
+                    let mut v = reader.poll(5)?;
+                    for _ in 0..4 {
+                        if escaped.len() < len {
+                            escaped.push(*COMMENT_TABLE.get((v % 96) as usize).ok_or(FumenError::Corrupt)? as char);
+                        }
+                        v /= 96;
+                    }
+                }
+                unescape(&escaped)
+            } else {
+                prev_comment.clone()
+            };
+
+            pages.push(FumenPage { board: field_to_board(&field), piece: piece.clone(), comment: comment.clone(), lock });
+
+            if lock {
+                if let Some(loc) = &piece {
+                    for (x, y) in loc.blocks() {
+                        if (0..10).contains(&x) && (0..FIELD_TOP).contains(&(y as i32)) {
+                            field[cell_index(x as i32, y as i32)] = piece_to_fumen(loc.piece);
+                        }
+                    }
+                }
+                clear_field_lines(&mut field);
+                if rise {
+                    field.copy_within(10..FIELD_BLOCKS, 0);
+                    field[FIELD_BLOCKS - 10..].fill(0);
+                }
+                if mirror {
+                    for row in field[..FIELD_BLOCKS - 10].chunks_mut(10) {
+                        row.reverse();
+                    }
+                }
+            }
+            prev_field = field;
+            prev_comment = comment;
+        }
+
+        Ok(Self { pages })
+    }
+
+    pub fn encode(&self) -> String {
+        let mut writer = Writer { data: vec![] };
+        let mut prev_field = [0u8; FIELD_BLOCKS];
+        let mut prev_comment = String::new();
+        let mut repeat_idx: Option<usize> = None;
+
+        for (i, page) in self.pages.iter().enumerate() {
+            let mut field = [0u8; FIELD_BLOCKS];
+            for y in 0..FIELD_TOP {
+                for x in 0..10 {
+                    let idx = cell_index(x, y);
+                    if page.board.cols[x as usize] & (1 << y) > 0 {
+                        field[idx] = if prev_field[idx] != 0 { prev_field[idx] } else { GRAY };
+                    }
+                }
+            }
+            // the garbage row isn't part of Board, keep whatever was there
+            field[FIELD_BLOCKS - 10..].copy_from_slice(&prev_field[FIELD_BLOCKS - 10..]);
+
+            let mut runs: Vec<(u64, u64)> = vec![];
+            for idx in 0..FIELD_BLOCKS {
+                let diff = field[idx] as u64 + 8 - prev_field[idx] as u64;
+                match runs.last_mut() {
+                    Some((d, n)) if *d == diff => *n += 1,
+                    _ => runs.push((diff, 0))
+                }
+            }
+            let changed = runs.len() > 1 || runs[0].0 != 8;
+            match repeat_idx {
+                Some(r) if !changed && writer.data[r] < 63 => writer.data[r] += 1,
+                _ => {
+                    for (diff, n) in runs {
+                        writer.push(diff * FIELD_BLOCKS as u64 + n, 2);
+                    }
+                    repeat_idx = if changed { None } else {
+                        writer.push(0, 1);
+                        Some(writer.data.len() - 1)
+                    };
+                }
+            }
+
+            let comment_flag = page.comment != prev_comment;
+            let (kind, rotation, pos) = match &page.piece {
+                Some(loc) => {
+                    let (x, y) = loc.to_fumen();
+                    let (x, y) = (x as i32, y as i32);
+                    let rotation = match loc.rotation {
+                        Rotation::South => 0,
+                        Rotation::East => 1,
+                        Rotation::North => 2,
+                        Rotation::West => 3
+                    };
+                    (piece_to_fumen(loc.piece) as u64, rotation, ((FIELD_TOP - y - 1) * 10 + x) as u64)
+                }
+                None => (0, 0, 0)
+            };
+            let flags = (!page.lock as u64) << 4 | (comment_flag as u64) << 3 | ((i == 0) as u64) << 2;
+            writer.push(((flags * FIELD_BLOCKS as u64 + pos) * 4 + rotation) * 8 + kind, 3);
+
+            if comment_flag {
+                let escaped = escape(&page.comment);
+                let escaped = &escaped.as_bytes()[..escaped.len().min(4095)];
+                writer.push(escaped.len() as u64, 2);
+                for chunk in escaped.chunks(4) {
+                    let v = chunk.iter().rev().fold(0, |acc, &c| {
+                        acc * 96 + COMMENT_TABLE.iter().position(|&t| t == c).unwrap_or(0) as u64
+                    });
+                    writer.push(v, 5);
+                }
+            }
+
+            if page.lock {
+                if let Some(loc) = &page.piece {
+                    for (x, y) in loc.blocks() {
+                        if (0..10).contains(&x) && (0..FIELD_TOP).contains(&(y as i32)) {
+                            field[cell_index(x as i32, y as i32)] = piece_to_fumen(loc.piece);
+                        }
+                    }
+                }
+                clear_field_lines(&mut field);
+            }
+            prev_field = field;
+            prev_comment = page.comment.clone();
+        }
+
+        // a '?' goes after the first 42 characters and then every 47
+        let data = String::from_utf8(writer.data.iter().map(|&v| ENCODE_TABLE[v as usize]).collect()).unwrap();
+        let mut out = String::from("v115@");
+        for (i, c) in data.chars().enumerate() {
+            if i >= 42 && (i - 42) % 47 == 0 {
+                out.push('?');
+            }
+            out.push(c);
+        }
+        out
+    }
+}
+
+impl FumenPage {
+    // hold and queue from a quiz comment like `#Q=[S](T)ZLJO`. the current piece is queue[0]
+    pub fn queue(&self) -> Option<(Option<Piece>, Vec<Piece>)> {
+        parse_quiz(&self.comment)
+    }
+}
+
+pub fn quiz_comment(hold: Option<Piece>, queue: &[Piece]) -> String {
+    if queue.is_empty() && hold.is_none() {
+        return String::new();
+    }
+    let name = |p: Piece| format!("{p:?}");
+    format!(
+        "#Q=[{}]({}){}",
+        hold.map(name).unwrap_or_default(),
+        queue.first().copied().map(name).unwrap_or_default(),
+        queue.iter().skip(1).copied().map(name).collect::<String>()
+    )
+}
+
+pub fn parse_quiz(comment: &str) -> Option<(Option<Piece>, Vec<Piece>)> {
+    let rest = comment.trim().strip_prefix("#Q=[")?;
+    let (hold, rest) = rest.split_once("](")?;
+    let (current, next) = rest.split_once(')')?;
+    let parse = |s: &str| s.chars().map(Piece::from_letter).collect::<Option<Vec<Piece>>>();
+    let hold = parse(hold)?.first().copied();
+    let mut queue = parse(current)?;
+    queue.extend(parse(next.split(';').next().unwrap_or(""))?);
+    Some((hold, queue))
+}
+
+const fn piece_to_fumen(piece: Piece) -> u8 {
+    match piece {
+        Piece::I => 1,
+        Piece::L => 2,
+        Piece::O => 3,
+        Piece::Z => 4,
+        Piece::T => 5,
+        Piece::J => 6,
+        Piece::S => 7
+    }
+}
+
+const fn piece_from_fumen(v: u8) -> Option<Piece> {
+    match v {
+        1 => Some(Piece::I),
+        2 => Some(Piece::L),
+        3 => Some(Piece::O),
+        4 => Some(Piece::Z),
+        5 => Some(Piece::T),
+        6 => Some(Piece::J),
+        7 => Some(Piece::S),
+        _ => None
+    }
+}
+
+fn cell_index(x: i32, y: i32) -> usize {
+    ((FIELD_TOP - y - 1) * 10 + x) as usize
+}
+
+fn field_to_board(field: &[u8; FIELD_BLOCKS]) -> Board {
+    let mut board = Board::new();
+    for y in 0..FIELD_TOP {
+        for x in 0..10 {
+            if field[cell_index(x, y)] != 0 {
+                board.cols[x as usize] |= 1 << y;
+            }
+        }
+    }
+    board
+}
+
+fn clear_field_lines(field: &mut [u8; FIELD_BLOCKS]) {
+    let mut rows: Vec<[u8; 10]> = field[..FIELD_BLOCKS - 10].chunks(10)
+        .map(|r| r.try_into().unwrap())
+        .filter(|r: &[u8; 10]| r.contains(&0))
+        .collect();
+    while rows.len() < FIELD_TOP as usize {
+        rows.insert(0, [0; 10]);
+    }
+    for (i, row) in rows.iter().enumerate() {
+        field[i * 10..i * 10 + 10].copy_from_slice(row);
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize
+}
+
+impl Reader<'_> {
+    fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    fn poll(&mut self, n: usize) -> Result<u64, FumenError> {
+        let mut v = 0;
+        for i in 0..n {
+            let c = *self.data.get(self.pos + i).ok_or(FumenError::Truncated)?;
+            let digit = ENCODE_TABLE.iter().position(|&t| t == c).ok_or(FumenError::Character(c as char))?;
+            v += (digit as u64) << (6 * i);
+        }
+        self.pos += n;
+        Ok(v)
+    }
+}
+
+struct Writer {
+    data: Vec<u8>
+}
+
+impl Writer {
+    fn push(&mut self, mut v: u64, n: usize) {
+        for _ in 0..n {
+            self.data.push((v % 64) as u8);
+            v /= 64;
+        }
+    }
+}
+
+// javascript's escape()/unescape(), which is what fumen runs comments through
+fn escape(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.encode_utf16() {
+        match char::from_u32(c as u32) {
+            Some(ch) if ch.is_ascii_alphanumeric() || "@*_+-./".contains(ch) => out.push(ch),
+            _ if c < 256 => out.push_str(&format!("%{c:02X}")),
+            _ => out.push_str(&format!("%u{c:04X}"))
+        }
+    }
+    out
+}
+
+fn unescape(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut units: Vec<u16> = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = |from: usize, len: usize| s.get(from..from + len).and_then(|h| u16::from_str_radix(h, 16).ok());
+        if bytes[i] == b'%' {
+            if bytes.get(i + 1) == Some(&b'u') && let Some(v) = hex(i + 2, 4) {
+                units.push(v);
+                i += 6;
+                continue;
+            }
+            if let Some(v) = hex(i + 1, 2) {
+                units.push(v);
+                i += 3;
+                continue;
+            }
+        }
+        units.push(bytes[i] as u16);
+        i += 1;
+    }
+    String::from_utf16_lossy(&units)
+}
@@ -1,23 +1,131 @@
-use super::data::{Spin, Board, Rotation, Piece, PieceLocation};
+use super::{
+    data::{Spin, Board, Rotation, Piece, PieceLocation},
+    rotation::RotationSystem
+};
 use bitboard_traits::BitboardTrait;
-use serde::Deserialize;
+use serde::{Serialize, Deserialize};
 use rand::Rng;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "ConfigFields")]
 pub struct GameConfig {
-    pub all_spin: bool
+    pub spins: SpinRule,
+    pub rotation: RotationSystem,
+    pub gravity: Gravity,
+    // inputs a piece can make while resting on the stack before it locks. None is unlimited
+    pub move_resets: Option<u8>,
+    // only used by search
+    pub speculation: GarbageSpeculation
 }
 
-#[derive(Debug, Clone, Deserialize)]
+// what GameConfig is read from. configs from before spin rules have `all_spin: bool` instead of `spins`, and only ever
+// had all-mini or all-spin
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct ConfigFields {
+    spins: Option<SpinRule>,
+    all_spin: Option<bool>,
+    rotation: RotationSystem,
+    gravity: Gravity,
+    move_resets: Option<u8>,
+    speculation: GarbageSpeculation
+}
+
+impl From<ConfigFields> for GameConfig {
+    fn from(f: ConfigFields) -> Self {
+        let spins = f.spins.unwrap_or(if f.all_spin == Some(true) { SpinRule::AllSpin } else { SpinRule::AllMini });
+        Self { spins, rotation: f.rotation, gravity: f.gravity, move_resets: f.move_resets, speculation: f.speculation }
+    }
+}
+
+impl GameConfig {
+    // whether pieces can reach everything the inputs allow, which is all the bitboard movegen knows how to do
+    pub const fn free_movement(&self) -> bool {
+        matches!(self.gravity, Gravity::Free) && self.move_resets.is_none()
+    }
+}
+
+// how search treats garbage it tanks, since it can't know where the hole will be
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GarbageSpeculation {
+    // one guess that doesn't change between runs: the hole lines up with the garbage already at the bottom, since most
+    // garbage comes in clean, or goes by the left wall if there is none
+    #[default]
+    Expected,
+    // the placement is scored with the hole in this many columns spread across the board, and the scores averaged. the
+    // search carries on from the expected hole
+    Sampled(u8)
+}
+
+// how a piece gets from spawn to where it locks
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Gravity {
+    // infinite softdrop and slow enough gravity that it never matters
+    #[default]
+    Free,
+    // 20g: the piece falls onto the stack when it spawns and after every input
+    Instant,
+    // no softdrop: the piece is moved around at spawn height and harddropped
+    HarddropOnly
+}
+
+// which placements count as spins. t spins always use the 3 corner rule; this decides what happens to everything else
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SpinRule {
+    // only t spins count
+    TSpins,
+    // tetr.io all-mini: an immobile non-T piece is a mini
+    #[default]
+    AllMini,
+    // an immobile non-T piece is a full spin, worth as much as a t spin
+    AllSpin,
+    // tetr.io all-mini+: all-mini, and an immobile T that fails the 3 corner rule is still a mini
+    MiniPlus
+}
+
+impl SpinRule {
+    // what an immobile non-T piece counts as
+    pub const fn immobile_spin(&self) -> Spin {
+        match self {
+            SpinRule::TSpins => Spin::None,
+            SpinRule::AllMini | SpinRule::MiniPlus => Spin::Mini,
+            SpinRule::AllSpin => Spin::Full
+        }
+    }
+
+    // relabels a spin under this rule, for locations that were found under a different one
+    pub const fn classify(&self, piece: Piece, spin: Spin) -> Spin {
+        match (piece, spin) {
+            (Piece::T, _) | (_, Spin::None) => spin,
+            _ => self.immobile_spin()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Game {
     pub board: Board,
     pub hold: Option<Piece>,
     pub b2b: i16, // if b2b goes above 65535 we are so cooked
     pub combo: i8, // combo < 30 so we should be safe here
-    pub incoming_garbage: u16 // in theory if the bot is afk this can exceed 65535. but who gaf lmao
+    pub incoming_garbage: u16, // in theory if the bot is afk this can exceed 65535. but who gaf lmao
+    #[serde(default)]
+    pub config: GameConfig
+}
+
+// the part of a game that changes as pieces are placed, with the config borrowed from the game it came from. it's Copy
+// and a few words big, so search keeps one per node and placing a piece never clones or allocates anything
+#[derive(Debug, Copy, Clone)]
+pub struct GameState<'a> {
+    pub board: Board,
+    pub hold: Option<Piece>,
+    pub b2b: i16,
+    pub combo: i8,
+    pub incoming_garbage: u16,
+    pub config: &'a GameConfig
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlacementInfo {
     pub lines_cleared: u8,
     pub lines_received: u16,
@@ -35,16 +143,109 @@ impl Game {
             hold: None,
             b2b: -1,
             combo: -1,
-            incoming_garbage: 0
+            incoming_garbage: 0,
+            config: GameConfig::default()
         }
     }
 
+    // simulation: plays the placement on this game, tanking garbage at a random column like a real game would
     pub fn advance(&mut self, next: Piece, loc: &PieceLocation) -> PlacementInfo {
+        self.advance_with(next, loc, || rand::rng().random_range(0..10))
+    }
+
+    // same as advance, but the caller picks the garbage hole column (only called if garbage is actually tanked)
+    pub fn advance_with(&mut self, next: Piece, loc: &PieceLocation, garbage_col: impl FnOnce() -> usize) -> PlacementInfo {
+        let mut state = GameState {
+            board: self.board,
+            hold: self.hold,
+            b2b: self.b2b,
+            combo: self.combo,
+            incoming_garbage: self.incoming_garbage,
+            config: &self.config
+        };
+        let info = state.advance_with(next, loc, garbage_col);
+        (self.board, self.hold, self.b2b, self.combo, self.incoming_garbage) = (state.board, state.hold, state.b2b, state.combo, state.incoming_garbage);
+        info
+    }
+
+    // for search, see GameState
+    pub fn state(&self) -> GameState<'_> {
+        GameState {
+            board: self.board,
+            hold: self.hold,
+            b2b: self.b2b,
+            combo: self.combo,
+            incoming_garbage: self.incoming_garbage,
+            config: &self.config
+        }
+    }
+
+    pub fn calculate_attack(&self, lines_cleared: u8, spin: Spin, b2b_clear: bool, pc: bool, surge: u16, combo: i8) -> u16 {
+        self.state().calculate_attack(lines_cleared, spin, b2b_clear, pc, surge, combo)
+    }
+
+    pub fn can_spawn_piece(&self, piece: Piece) -> bool {
+        self.state().can_spawn_piece(piece)
+    }
+
+    pub fn into_string(&self, loc: Option<&PieceLocation>) -> String {
+        let mut outstr: Vec<String> = vec![];
+        for y in (0..20).rev() {
+            let mut vstr = String::new();
+            let stat_str = if let Some(l) = loc {
+                let mut temp = self.board;
+                temp.put_piece(&l);
+                match y {
+                    5 => format!("spin: {:?}", l.spin),
+                    6 => format!("cleared: {}", temp.fold_and().count_ones()),
+                    _ => String::new()
+                }
+            } else {
+                String::new()
+            };
+            let stat_str = match y {
+                7 => format!("b2b: {}", self.b2b),
+                8 => format!("combo: {}", self.combo),
+                _ => stat_str
+            };
+            let formatting = match y {
+                7 => if self.b2b >= 3 { "\x1b[1;32m" } else { "" },
+                _ => ""
+            };
+            vstr.push_str(&format!("{formatting}{stat_str:>15}\x1b[0m  "));
+            vstr.push_str(if (y as u16) < self.incoming_garbage { "\x1b[31m▌\x1b[0m" } else { "\x1b[30m▌\x1b[0m" });
+            for x in 0..10 {
+                let mut c = if (self.board.cols[x as usize] & (1 << y)) > 0 { "🟩" } else { "⬜️" };
+                if let Some(l) = loc {
+                    if l.blocks().iter().any(|(bx, by)| *bx == x && *by == y) {
+                        c = if l.spin != Spin::None { "🟨" } else { "🟥" }
+                    }
+                }
+                vstr.push_str(c);
+            }
+            outstr.push(vstr);
+        }
+        outstr.join("\n")
+    }
+}
+
+impl GameState<'_> {
+    // the state after the placement, with garbage tanked at hole. self is left alone and nothing is random, so the same
+    // call always gives the same state
+    pub fn after(&self, next: Piece, loc: &PieceLocation, hole: usize) -> (Self, PlacementInfo) {
+        let mut state = *self;
+        let info = state.advance_with(next, loc, || hole);
+        (state, info)
+    }
+
+    // Game::advance_with does this on its own state
+    pub fn advance_with(&mut self, next: Piece, loc: &PieceLocation, garbage_col: impl FnOnce() -> usize) -> PlacementInfo {
         if loc.piece != next {
             self.hold = Some(next);
         }
         self.board.put_piece(&loc);
         let line_mask = self.board.remove_lines();
+        let spin = self.config.spins.classify(loc.piece, loc.spin);
 
         let mut info = PlacementInfo {
             lines_cleared: line_mask.count_ones() as u8,
@@ -52,7 +253,7 @@ impl Game {
             pc: false,
             b2b_clear: false,
             broke_surge: false,
-            spin: loc.spin,
+            spin,
             outgoing_attack: 0
         };
 
@@ -63,7 +264,7 @@ impl Game {
                 info.b2b_clear = true;
             }
             
-            if info.lines_cleared == 4 || loc.spin != Spin::None {
+            if info.lines_cleared == 4 || spin != Spin::None {
                 info.b2b_clear = true;
             }
 
@@ -78,11 +279,12 @@ impl Game {
                 self.b2b = -1;
             }
         } else {
-            let mut rng = rand::rng();
             self.combo = -1;
 
             let lines = self.incoming_garbage.min(8);
-            self.board.add_garbage(rng.random_range(0..10), lines);
+            if lines > 0 {
+                self.board.add_garbage(garbage_col(), lines);
+            }
             self.incoming_garbage -= lines;
             info.lines_received = lines;
         }
@@ -98,16 +300,14 @@ impl Game {
 
         let mut attack = 0;
         
-        attack += if spin == Spin::Full {
-            2 * lines_cleared as u16
-        } else {
-            match lines_cleared {
-                1 => 0,
-                2 => 1,
-                3 => 2,
-                4 => 4,
-                _ => unreachable!()
-            }
+        // minis (t spin minis, and other pieces under all-mini) send what a normal clear would, they only keep b2b
+        attack += match (spin, lines_cleared) {
+            (Spin::Full, _) => 2 * lines_cleared as u16,
+            (Spin::None | Spin::Mini, 1) => 0,
+            (Spin::None | Spin::Mini, 2) => 1,
+            (Spin::None | Spin::Mini, 3) => 2,
+            (Spin::None | Spin::Mini, 4) => 4,
+            _ => unreachable!()
         };
 
 
@@ -129,44 +329,4 @@ impl Game {
     pub fn can_spawn_piece(&self, piece: Piece) -> bool {
         !self.board.obstructed(&PieceLocation { piece, rotation: Rotation::North, spin: Spin::None, x: 4, y: 21})
     }
-
-    pub fn into_string(&self, loc: Option<&PieceLocation>) -> String {
-        let mut outstr: Vec<String> = vec![];
-        for y in (0..20).rev() {
-            let mut vstr = String::new();
-            let stat_str = if let Some(l) = loc {
-                let mut temp = self.board.clone();
-                temp.put_piece(&l);
-                match y {
-                    5 => format!("spin: {:?}", l.spin),
-                    6 => format!("cleared: {}", temp.fold_and().count_ones()),
-                    _ => String::new()
-                }
-            } else {
-                String::new()
-            };
-            let stat_str = match y {
-                7 => format!("b2b: {}", self.b2b),
-                8 => format!("combo: {}", self.combo),
-                _ => stat_str
-            };
-            let formatting = match y {
-                7 => if self.b2b >= 3 { "\x1b[1;32m" } else { "" },
-                _ => ""
-            };
-            vstr.push_str(&format!("{formatting}{stat_str:>15}\x1b[0m  "));
-            vstr.push_str(if (y as u16) < self.incoming_garbage { "\x1b[31m▌\x1b[0m" } else { "\x1b[30m▌\x1b[0m" });
-            for x in 0..10 {
-                let mut c = if (self.board.cols[x as usize] & (1 << y)) > 0 { "🟩" } else { "⬜️" };
-                if let Some(l) = loc {
-                    if l.blocks().iter().any(|(bx, by)| *bx == x && *by == y) {
-                        c = if l.spin != Spin::None { "🟨" } else { "🟥" }
-                    }
-                }
-                vstr.push_str(c);
-            }
-            outstr.push(vstr);
-        }
-        outstr.join("\n")
-    }
-}
\ No newline at end of file
+}
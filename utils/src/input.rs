@@ -0,0 +1,318 @@
+use std::{cmp::Reverse, collections::{BinaryHeap, HashMap, HashSet}};
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    data::{Board, Piece, PieceLocation},
+    game::{GameConfig, Gravity},
+    movegen::{canonical, locked, spawn_location, try_shift, try_rotate, sonic_drop},
+    replay::{Key, ReplayEvent, TimedEvent}
+};
+
+// turning a placement into the keys that get the piece there, and checking that a key sequence does what it should.
+// the names are what the bot api sends to the client, so don't rename them
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MovementAction {
+    Spawn,
+    TapLeft,
+    TapRight,
+    DASLeft,
+    DASRight,
+    Softdrop,
+    Hold,
+    RotateCW,
+    RotateCCW,
+    Rotate180,
+    Harddrop
+}
+
+impl MovementAction {
+    // the key pressed for it. None for spawn, which isn't an input
+    pub const fn key(&self) -> Option<Key> {
+        Some(match self {
+            MovementAction::Spawn => return None,
+            MovementAction::TapLeft | MovementAction::DASLeft => Key::MoveLeft,
+            MovementAction::TapRight | MovementAction::DASRight => Key::MoveRight,
+            MovementAction::Softdrop => Key::SoftDrop,
+            MovementAction::Hold => Key::Hold,
+            MovementAction::RotateCW => Key::RotateCW,
+            MovementAction::RotateCCW => Key::RotateCCW,
+            MovementAction::Rotate180 => Key::Rotate180,
+            MovementAction::Harddrop => Key::HardDrop
+        })
+    }
+}
+
+// handling settings, in frames
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Handling {
+    // frames a direction is held before it starts repeating
+    pub das: f32,
+    // frames between repeats, 0 moves to the wall instantly
+    pub arr: f32,
+    // rows per frame while softdropping, infinite drops instantly
+    pub sdf: f32,
+    // frames das is paused for after a rotation
+    pub dcd: f32
+}
+
+impl Default for Handling {
+    fn default() -> Self {
+        Self { das: 10.0, arr: 2.0, sdf: f32::INFINITY, dcd: 1.0 }
+    }
+}
+
+impl Handling {
+    // tetr.io's handling menu. its sdf multiplies gravity (but is at least 0.05 rows a frame) and 41 drops instantly, the
+    // same as replays are simulated
+    pub fn from_tetrio(das: f32, arr: f32, sdf: f32, dcd: f32, gravity: f32) -> Self {
+        let sdf = if sdf >= 41.0 { f32::INFINITY } else { gravity.max(0.05) * sdf };
+        Self { das, arr, sdf, dcd }
+    }
+}
+
+// what the keys are picked to minimise
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Finesse {
+    // fewest keypresses
+    Keys,
+    // fewest keypresses without holding a direction, for clients that only tap
+    Taps,
+    // fastest with the given handling
+    Frames(Handling)
+}
+
+impl Finesse {
+    // cost of an input that moved the piece `cells` cells (columns for shifts, rows for softdrop). None if it isn't allowed
+    fn cost(&self, action: MovementAction, cells: u32) -> Option<u32> {
+        use MovementAction::*;
+        match (self, action) {
+            (Finesse::Taps, DASLeft | DASRight) => None,
+            (Finesse::Keys | Finesse::Taps, _) => Some(1),
+            (Finesse::Frames(h), DASLeft | DASRight) => Some((h.das + h.arr * cells.saturating_sub(1) as f32).ceil().max(1.0) as u32),
+            (Finesse::Frames(h), Softdrop) => Some((cells as f32 / h.sdf).ceil().max(1.0) as u32),
+            (Finesse::Frames(_), _) => Some(1)
+        }
+    }
+}
+
+struct Step {
+    loc: PieceLocation,
+    parent: usize,
+    action: MovementAction
+}
+
+pub(crate) struct Placement {
+    pub loc: PieceLocation,
+    // with the harddrop
+    pub cost: u32,
+    // step it was harddropped from
+    step: usize
+}
+
+// the cheapest way to every placement of a piece, by canonical location
+pub(crate) struct Paths {
+    steps: Vec<Step>,
+    pub placements: HashMap<(i8, i8, u8, u8), Placement>
+}
+
+impl Paths {
+    // keys from spawn to the harddrop, without the spawn itself
+    fn keys(&self, mut step: usize) -> Vec<MovementAction> {
+        let mut keys = vec![MovementAction::Harddrop];
+        while step != 0 {
+            keys.push(self.steps[step].action);
+            step = self.steps[step].parent;
+        }
+        keys.reverse();
+        keys
+    }
+}
+
+pub(crate) fn key(l: &PieceLocation) -> (i8, i8, u8, u8) {
+    (l.x, l.y, l.rotation as u8, l.spin as u8)
+}
+
+// dijkstra over single inputs from spawn. every location the piece can be in is harddropped to get the placements, so
+// they come out labelled the same way movegen labels them. move reset limits are not taken into account
+pub(crate) fn search(board: &Board, piece: Piece, force: bool, config: &GameConfig, cost: impl Fn(MovementAction, u32) -> Option<u32>) -> Paths {
+    let mut paths = Paths { steps: vec![], placements: HashMap::new() };
+    let Some(spawn) = spawn_location(board, piece, force) else { return paths };
+
+    let fall = |l: PieceLocation| if config.gravity == Gravity::Instant { sonic_drop(board, &l) } else { l };
+    let das = |l: &PieceLocation, dx: i8| {
+        let mut l = try_shift(board, l, dx).map(fall)?;
+        while let Some(next) = try_shift(board, &l, dx).map(fall) {
+            l = next;
+        }
+        Some(l)
+    };
+    let grounded = |l: &PieceLocation| board.collides(&PieceLocation { y: l.y - 1, ..l.clone() });
+
+    let mut best = HashMap::new();
+    let mut expanded = HashSet::new();
+    let mut queue = BinaryHeap::new();
+
+    let start = fall(spawn);
+    best.insert(key(&start), 0);
+    paths.steps.push(Step { loc: start, parent: 0, action: MovementAction::Spawn });
+    queue.push(Reverse((0, 0)));
+
+    while let Some(Reverse((n, idx))) = queue.pop() {
+        let loc = paths.steps[idx].loc.clone();
+        if !expanded.insert(key(&loc)) {
+            continue;
+        }
+
+        let dropped = sonic_drop(board, &loc);
+        if let Some(c) = cost(MovementAction::Harddrop, (loc.y - dropped.y) as u32) {
+            let lock = locked(board, &dropped, config.spins);
+            let total = n + c;
+            let entry = paths.placements.entry(key(&lock)).or_insert(Placement { loc: lock, cost: u32::MAX, step: idx });
+            if total < entry.cost {
+                (entry.cost, entry.step) = (total, idx);
+            }
+        }
+
+        let moves = [
+            (MovementAction::TapLeft, try_shift(board, &loc, -1).map(fall)),
+            (MovementAction::TapRight, try_shift(board, &loc, 1).map(fall)),
+            (MovementAction::DASLeft, das(&loc, -1)),
+            (MovementAction::DASRight, das(&loc, 1)),
+            (MovementAction::RotateCW, try_rotate(board, &loc, loc.rotation.rotate_cw(), config).map(fall)),
+            (MovementAction::RotateCCW, try_rotate(board, &loc, loc.rotation.rotate_ccw(), config).map(fall)),
+            (MovementAction::Rotate180, try_rotate(board, &loc, loc.rotation.rotate_180(), config).map(fall)),
+            (MovementAction::Softdrop, (config.gravity == Gravity::Free && !grounded(&loc)).then_some(dropped))
+        ];
+        for (action, next) in moves {
+            let Some(next) = next else { continue };
+            let cells = match action {
+                MovementAction::Softdrop => loc.y - next.y,
+                _ => next.x - loc.x
+            }.unsigned_abs() as u32;
+            let Some(c) = cost(action, cells) else { continue };
+            let entry = best.entry(key(&next)).or_insert(u32::MAX);
+            if n + c < *entry {
+                *entry = n + c;
+                paths.steps.push(Step { loc: next, parent: idx, action });
+                queue.push(Reverse((n + c, paths.steps.len() - 1)));
+            }
+        }
+    }
+    paths
+}
+
+// the cheapest keys that put the piece at target (as movegen reports it, so canonical and with the same spin), ending
+// in a harddrop. None if it can't get there
+pub fn keygen(board: &Board, target: &PieceLocation, finesse: &Finesse, config: &GameConfig) -> Result<Vec<MovementAction>, KeygenError> {
+    if spawn_location(board, target.piece, true).is_none() {
+        return Err(KeygenError::Blocked(target.piece));
+    }
+    let paths = search(board, target.piece, true, config, |action, cells| finesse.cost(action, cells));
+    let placement = paths.placements.get(&key(&canonical(target))).ok_or_else(|| KeygenError::Unreachable(target.clone()))?;
+    Ok(paths.keys(placement.step))
+}
+
+#[derive(Debug, Clone)]
+pub enum KeygenError {
+    // the piece can't spawn, so there are no keys for anything
+    Blocked(Piece),
+    // no inputs lock the piece there (with that spin), or it doesn't fit at all
+    Unreachable(PieceLocation)
+}
+
+impl std::fmt::Display for KeygenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeygenError::Blocked(piece) => write!(f, "{piece:?} can't spawn"),
+            KeygenError::Unreachable(loc) => write!(f, "no keys reach {loc:?}")
+        }
+    }
+}
+
+impl std::error::Error for KeygenError {}
+
+// moves the piece for one input (harddrop aside). None if the gravity doesn't allow the input
+fn apply(board: &Board, loc: &PieceLocation, action: MovementAction, config: &GameConfig) -> Option<PieceLocation> {
+    let fall = |l: PieceLocation| if config.gravity == Gravity::Instant { sonic_drop(board, &l) } else { l };
+    let moved = match action {
+        MovementAction::TapLeft => try_shift(board, loc, -1),
+        MovementAction::TapRight => try_shift(board, loc, 1),
+        MovementAction::DASLeft | MovementAction::DASRight => {
+            let dx = if action == MovementAction::DASLeft { -1 } else { 1 };
+            let mut loc = loc.clone();
+            while let Some(next) = try_shift(board, &loc, dx).map(fall) {
+                loc = next;
+            }
+            Some(loc)
+        }
+        MovementAction::RotateCW => try_rotate(board, loc, loc.rotation.rotate_cw(), config),
+        MovementAction::RotateCCW => try_rotate(board, loc, loc.rotation.rotate_ccw(), config),
+        MovementAction::Rotate180 => try_rotate(board, loc, loc.rotation.rotate_180(), config),
+        MovementAction::Softdrop if config.gravity == Gravity::HarddropOnly => return None,
+        MovementAction::Softdrop => Some(sonic_drop(board, loc)),
+        MovementAction::Spawn | MovementAction::Hold | MovementAction::Harddrop => None
+    };
+    Some(moved.map_or_else(|| loc.clone(), fall))
+}
+
+// plays keys from spawn and returns where the piece locks, labelled like movegen labels placements. spawn and hold are
+// skipped; None if there is no harddrop, anything after it, or an input the gravity doesn't allow
+pub fn replay_keys(board: &Board, piece: Piece, keys: &[MovementAction], config: &GameConfig) -> Option<PieceLocation> {
+    let spawn = spawn_location(board, piece, true)?;
+    let mut loc = if config.gravity == Gravity::Instant { sonic_drop(board, &spawn) } else { spawn };
+    for (i, &action) in keys.iter().enumerate() {
+        if action == MovementAction::Harddrop {
+            return (i == keys.len() - 1).then(|| locked(board, &sonic_drop(board, &loc), config.spins));
+        }
+        loc = apply(board, &loc, action, config)?;
+    }
+    None
+}
+
+// whether keys put the piece exactly at target, spin included
+pub fn verify(board: &Board, keys: &[MovementAction], target: &PieceLocation, config: &GameConfig) -> bool {
+    replay_keys(board, target.piece, keys, config).is_some_and(|l| key(&l) == key(&canonical(target)))
+}
+
+// keydown/keyup events that play keys with the given handling, in frames from when the piece spawns. instant inputs are
+// pressed and released on the same subframe; das and softdrop are held until the piece gets where keys expect it.
+// with a pps, everything is pushed back so the harddrop lands 60 / pps frames in. None if keys don't replay (see replay_keys)
+pub fn timeline(board: &Board, piece: Piece, keys: &[MovementAction], config: &GameConfig, handling: &Handling, pps: Option<f32>) -> Option<Vec<TimedEvent>> {
+    replay_keys(board, piece, keys, config)?;
+    let spawn = spawn_location(board, piece, true)?;
+    let mut loc = if config.gravity == Gravity::Instant { sonic_drop(board, &spawn) } else { spawn };
+
+    let mut held = vec![];
+    let mut after_rotation = false;
+    for &action in keys {
+        let next = apply(board, &loc, action, config).unwrap_or_else(|| loc.clone());
+        let frames = match action {
+            // one more frame for the last shift to be processed
+            MovementAction::DASLeft | MovementAction::DASRight => {
+                let cells = (next.x - loc.x).unsigned_abs() as f32;
+                handling.das + handling.arr * (cells - 1.0).max(0.0) + if after_rotation { handling.dcd } else { 0.0 } + 1.0
+            }
+            // an instant softdrop only needs a subframe of the frame to be simulated
+            MovementAction::Softdrop if handling.sdf.is_infinite() => 0.1,
+            MovementAction::Softdrop => ((loc.y - next.y) as f32 / handling.sdf).ceil() + 1.0,
+            _ => 0.0
+        };
+        held.push((action, frames as f64));
+        after_rotation = matches!(action, MovementAction::RotateCW | MovementAction::RotateCCW | MovementAction::Rotate180);
+        loc = next;
+    }
+
+    let total: f64 = held.iter().map(|&(_, f)| f).sum();
+    let mut t = pps.map_or(0.0, |pps| (60.0 / pps as f64 - total).max(0.0));
+    let at = |t: f64, event| TimedEvent { frame: t.floor() as u32, subframe: t.fract(), event };
+    let mut events = vec![];
+    for (action, frames) in held {
+        let Some(key) = action.key() else { continue };
+        events.push(at(t, ReplayEvent::KeyDown(key)));
+        t += frames;
+        events.push(at(t, ReplayEvent::KeyUp(key)));
+    }
+    Some(events)
+}
@@ -1,4 +1,12 @@
+pub mod ascii;
+pub mod cheese;
 pub mod data;
+pub mod fumen;
 pub mod game;
+pub mod input;
 pub mod movegen;
-pub mod queue;
\ No newline at end of file
+pub mod perft;
+pub mod queue;
+pub mod reference;
+pub mod replay;
+pub mod rotation;
\ No newline at end of file
@@ -1,7 +1,12 @@
+use std::collections::{HashMap, HashSet, VecDeque};
 use bitboard_derive::Bitboard;
 use bitboard_traits::BitboardTrait;
 
-use crate::data::{Piece, Rotation, Spin, Board, PieceLocation, LUT, ROT};
+use crate::{
+    data::{Piece, Rotation, Spin, Board, PieceLocation, LUT, ROT},
+    game::{GameConfig, Gravity, SpinRule},
+    input::{self, MovementAction}
+};
 
 pub const SPAWN_ROW: i8 = 21;
 pub const SPAWN_COL: usize = 4;
@@ -16,13 +21,13 @@ impl CollisionMap {
     pub fn new(board: &Board, piece: Piece, rotation: Rotation) -> Self {
         let mut obstructed = [0u64; 10];
         for (dx, dy) in LUT[piece as usize][rotation as usize] {
-            for x in 0..10 {
-                let c = board.cols.get(x + dx as usize).copied().unwrap_or(!0);
+            for x in 0..10usize {
+                let c = board.cols.get(x.wrapping_add(dx as usize)).copied().unwrap_or(!0);
                 let c = match dy.is_negative() {
                     true => !(!c << -dy),
                     false => c >> dy
                 };
-                obstructed[x as usize] |= c;
+                obstructed[x] |= c;
             }
         }
         Self { cols: obstructed }
@@ -44,26 +49,32 @@ pub const fn bb_low(x: i8) -> u64 { (1u64 << x) - 1 }
 // see below explanation of the remaining variable
 pub const fn xrot_idx(x: i8, rot: i8) -> u64 { bb(4 * x + rot) }
 
-pub fn movegen(arena: &mut Vec<PieceLocation>, board: &Board, main_piece: Piece, hold_piece: Option<Piece>, force: bool) -> usize {
+pub fn movegen(arena: &mut Vec<PieceLocation>, board: &Board, main_piece: Piece, hold_piece: Option<Piece>, force: bool, config: &GameConfig) -> usize {
     let idx = arena.len();
-    movegen_piece(arena, board, main_piece, force);
+    movegen_piece(arena, board, main_piece, force, config);
     if let Some(h) = hold_piece {
-        movegen_piece(arena, board, h, force);
+        movegen_piece(arena, board, h, force, config);
     }
     idx
 }
 
-pub fn movegen_piece(arena: &mut Vec<PieceLocation>, board: &Board, piece: Piece, force: bool) -> usize {
+pub fn movegen_piece(arena: &mut Vec<PieceLocation>, board: &Board, piece: Piece, force: bool, config: &GameConfig) -> usize {
     let idx = arena.len();
+    if !config.free_movement() {
+        movegen_piece_limited(arena, board, piece, force, config);
+        return idx;
+    }
     match piece {
-        Piece::J | Piece::L | Piece::S | Piece::Z | Piece::I => movegen_piece_nospin(arena, board, ROT.map(|r| CollisionMap::new(board, piece, r)), piece, force),
-        Piece::T => movegen_piece_t(arena, board, ROT.map(|r| CollisionMap::new(board, piece, r)), force),
+        Piece::J | Piece::L | Piece::S | Piece::Z | Piece::I => movegen_piece_nospin(arena, board, ROT.map(|r| CollisionMap::new(board, piece, r)), piece, force, config),
+        Piece::T => movegen_piece_t(arena, board, ROT.map(|r| CollisionMap::new(board, piece, r)), force, config),
         Piece::O => movegen_piece_o(arena, board, force)
     }
     idx
 }
 
-fn movegen_piece_nospin(arena: &mut Vec<PieceLocation>, board: &Board, cm: [CollisionMap; 4], piece: Piece, force: bool) {
+fn movegen_piece_nospin(arena: &mut Vec<PieceLocation>, board: &Board, cm: [CollisionMap; 4], piece: Piece, force: bool, config: &GameConfig) {
+    let rs = config.rotation;
+    let immobile_spin = config.spins.immobile_spin();
     // let cm = ROT.map(|r| CollisionMap::new(board, piece, r));
     let mut searched = cm.clone().map(|x| x.as_board());
     let mut to_search: [Board; 4] = std::array::from_fn(|_| Board::new());
@@ -76,7 +87,7 @@ fn movegen_piece_nospin(arena: &mut Vec<PieceLocation>, board: &Board, cm: [Coll
     for rot in ROT {
         for x in 0..10 {
             fullspinmap[rot as usize][x] = !cm[rot as usize][x] & (
-                cm[rot as usize].cols.get(x - 1).copied().unwrap_or(!0)
+                cm[rot as usize].cols.get(x.wrapping_sub(1)).copied().unwrap_or(!0)
                 & cm[rot as usize].cols.get(x + 1).copied().unwrap_or(!0)
                 & (cm[rot as usize][x] << 1 | 1)
                 & cm[rot as usize][x] >> 1
@@ -112,6 +123,8 @@ fn movegen_piece_nospin(arena: &mut Vec<PieceLocation>, board: &Board, cm: [Coll
         let spawn_bit = xrot_idx(SPAWN_COL as i8, Rotation::North as i8);
         remaining |= spawn_bit;
         to_search[Rotation::North as usize][SPAWN_COL] |= spawn;
+        // no bound when starting from spawn
+        max_moves = u32::MAX;
     } else {
         for x in 0..10 {
             for rot in ROT {
@@ -122,8 +135,10 @@ fn movegen_piece_nospin(arena: &mut Vec<PieceLocation>, board: &Board, cm: [Coll
                 let y = 64 - col.leading_zeros();
                 let surface = bb_low(SPAWN_ROW) & !bb_low(y as i8);
 
+                // only the landing spot counts as searched. the rest of the surface can still be reached mid-air
+                // by shifting or kicking from a neighbouring column, and kicks from there can reach new spots
                 to_search[rot as usize][x] = surface & ((cm[rot as usize][x] << 1) | 1);
-                searched[rot as usize][x] |= surface;
+                searched[rot as usize][x] |= to_search[rot as usize][x];
                 remaining |= xrot_idx(x as i8, rot as i8);
 
                 // moveset[rot as usize][x] |= bb(y as i8);
@@ -182,7 +197,7 @@ fn movegen_piece_nospin(arena: &mut Vec<PieceLocation>, board: &Board, cm: [Coll
                 moveset[canonical_rot][canonical_x] |= m;
                 max_moves -= m.count_ones();
                 while m != 0 {
-                    arena.push(PieceLocation { piece, x: canonical_x as i8, y: m.trailing_zeros() as i8, rotation: unsafe { std::mem::transmute(canonical_rot as u8) }, spin: if fullspinmap[canonical_rot][canonical_x] & (m & m.wrapping_neg()) == 0 { Spin::None } else { Spin::Mini }});
+                    arena.push(PieceLocation { piece, x: canonical_x as i8, y: m.trailing_zeros() as i8, rotation: unsafe { std::mem::transmute(canonical_rot as u8) }, spin: if fullspinmap[canonical_rot][canonical_x] & (m & m.wrapping_neg()) == 0 { Spin::None } else { immobile_spin }});
                     m &= m - 1;
                 }
                 if max_moves == 0 {
@@ -211,7 +226,7 @@ fn movegen_piece_nospin(arena: &mut Vec<PieceLocation>, board: &Board, cm: [Coll
             let mut current = to_search[rot][x];
             let from: Rotation = unsafe { std::mem::transmute(rot as u8) };
             let to: Rotation = unsafe { std::mem::transmute(to as u8) };
-            for (kx, ky) in kicks(piece, from, to) {
+            for (kx, ky) in rs.kicks(piece, from, to) {
                 let nx = x as i8 + kx;
                 if nx < 0 || nx > 9 {
                     continue;
@@ -231,7 +246,7 @@ fn movegen_piece_nospin(arena: &mut Vec<PieceLocation>, board: &Board, cm: [Coll
         let mut current = to_search[rot][x];
         let from: Rotation = unsafe { std::mem::transmute(rot as u8) };
         let to: Rotation = unsafe { std::mem::transmute(((rot + 2) & 3) as u8) };
-        for (kx, ky) in kicks_180(piece, from, to) {
+        for (kx, ky) in rs.kicks(piece, from, to) {
             let nx = x as i8 + kx;
             if nx < 0 || nx > 9 {
                 continue;
@@ -253,9 +268,12 @@ fn movegen_piece_nospin(arena: &mut Vec<PieceLocation>, board: &Board, cm: [Coll
     }
 }
 
-fn movegen_piece_t(arena: &mut Vec<PieceLocation>, board: &Board, cm: [CollisionMap; 4], force: bool) {
+fn movegen_piece_t(arena: &mut Vec<PieceLocation>, board: &Board, cm: [CollisionMap; 4], force: bool, config: &GameConfig) {
+    let rs = config.rotation;
     let mut fullspinmap: [Board; 4] = std::array::from_fn(|_| Board::new());
     let mut spinmap: [Board; 4] = std::array::from_fn(|_| Board::new());
+    // immobile spots that fail the 3 corner rule, only minis under all-mini+ (never upgraded by the last kick)
+    let mut minimap: [Board; 4] = std::array::from_fn(|_| Board::new());
 
     for x in 0..10 {
         let c = [
@@ -273,12 +291,14 @@ fn movegen_piece_t(arena: &mut Vec<PieceLocation>, board: &Board, cm: [Collision
             spinmap[rot as usize][x] = spins;
             if cm[rot as usize][x] != !0 {
                 fullspinmap[rot as usize][x] = spins & c[rot as usize] & c[rot.rotate_cw() as usize];
-                // spinmap[rot as usize][x] = !cm[rot as usize][x] & (
-                //     cm[rot as usize].cols.get(x - 1).copied().unwrap_or(!0)
-                //     & cm[rot as usize].cols.get(x + 1).copied().unwrap_or(!0)
-                //     & (cm[rot as usize][x] << 1 | 1)
-                //     & cm[rot as usize][x] >> 1
-                // );
+                if config.spins == SpinRule::MiniPlus {
+                    minimap[rot as usize][x] = !spins & !cm[rot as usize][x] & (
+                        cm[rot as usize].cols.get(x.wrapping_sub(1)).copied().unwrap_or(!0)
+                        & cm[rot as usize].cols.get(x + 1).copied().unwrap_or(!0)
+                        & (cm[rot as usize][x] << 1 | 1)
+                        & cm[rot as usize][x] >> 1
+                    );
+                }
             }
         }
     }
@@ -316,10 +336,10 @@ fn movegen_piece_t(arena: &mut Vec<PieceLocation>, board: &Board, cm: [Collision
                 let surface = bb_low(SPAWN_ROW) & !bb_low(y as i8);
 
                 to_search[rot as usize][x] = surface & ((cm[rot as usize][x] << 1) | 1);
-                searched[rot as usize][x] |= surface;
+                searched[rot as usize][x] |= to_search[rot as usize][x];
                 remaining |= xrot_idx(x as i8, rot as i8);
 
-                spinloc[Spin::None as usize][rot as usize][x] |= surface;
+                spinloc[Spin::None as usize][rot as usize][x] |= to_search[rot as usize][x];
             }
         }
     }
@@ -342,19 +362,20 @@ fn movegen_piece_t(arena: &mut Vec<PieceLocation>, board: &Board, cm: [Collision
 
         // horizontal shifts
         if x > 0 {
+            // label before filtering out searched spots, a shift can reach a spot that was already found with a spin
+            spinloc[Spin::None as usize][rot][x - 1] |= to_search[rot][x] & !cm[rot][x - 1];
             let m = to_search[rot][x] & !searched[rot][x - 1];
             if m != 0 {
                 to_search[rot][x - 1] |= m;
                 remaining |= xrot_idx(x as i8 - 1, rot as i8);
-                spinloc[Spin::None as usize][rot][x - 1] |= m;
             }
         }
         if x < 9 {
+            spinloc[Spin::None as usize][rot][x + 1] |= to_search[rot][x] & !cm[rot][x + 1];
             let m = to_search[rot][x] & !searched[rot][x + 1];
             if m != 0 {
                 to_search[rot][x + 1] |= m;
                 remaining |= xrot_idx(x as i8 + 1, rot as i8);
-                spinloc[Spin::None as usize][rot][x + 1] |= m;
             }
         }
 
@@ -362,9 +383,8 @@ fn movegen_piece_t(arena: &mut Vec<PieceLocation>, board: &Board, cm: [Collision
             let mut current = to_search[rot][x];
             let from: Rotation = unsafe { std::mem::transmute(rot as u8) };
             let to: Rotation = unsafe { std::mem::transmute(to as u8) };
-            let kcks = kicks(Piece::T, from, to);
-            for i in 0..5 {
-                let (kx, ky) = kcks[i];
+            let full_kick = rs.full_spin_kick(Piece::T, from, to);
+            for (i, (kx, ky)) in rs.kicks(Piece::T, from, to).enumerate() {
                 let nx = x as i8 + kx;
                 if nx < 0 || nx > 9 {
                     continue;
@@ -374,9 +394,11 @@ fn movegen_piece_t(arena: &mut Vec<PieceLocation>, board: &Board, cm: [Collision
                 current ^= (m << 3) >> (ky + 3);
 
                 let spins = m & spinmap[to as usize][nx as usize];
-                spinloc[Spin::None as usize][to as usize][nx as usize] |= m ^ spins;
+                let minis = m & minimap[to as usize][nx as usize];
+                spinloc[Spin::None as usize][to as usize][nx as usize] |= m ^ spins ^ minis;
+                spinloc[Spin::Mini as usize][to as usize][nx as usize] |= minis;
 
-                if i >= 4 {
+                if Some(i) == full_kick {
                     spinloc[Spin::Full as usize][to as usize][nx as usize] |= spins;
                 } else {
                     let fullspins = fullspinmap[to as usize][nx as usize];
@@ -395,9 +417,7 @@ fn movegen_piece_t(arena: &mut Vec<PieceLocation>, board: &Board, cm: [Collision
         let mut current = to_search[rot][x];
         let from: Rotation = unsafe { std::mem::transmute(rot as u8) };
         let to: Rotation = unsafe { std::mem::transmute(((rot + 2) & 3) as u8) };
-        let kcks180 = kicks_180(Piece::T, from, to);
-        for i in 0..6 {
-            let (kx, ky) = kcks180[i];
+        for (kx, ky) in rs.kicks(Piece::T, from, to) {
             let nx = x as i8 + kx;
             if nx < 0 || nx > 9 {
                 continue;
@@ -407,7 +427,9 @@ fn movegen_piece_t(arena: &mut Vec<PieceLocation>, board: &Board, cm: [Collision
             current ^= (m << 3) >> (ky + 3);
 
             let spins = m & spinmap[to as usize][nx as usize];
-            spinloc[Spin::None as usize][to as usize][nx as usize] |= m ^ spins;
+            let minis = m & minimap[to as usize][nx as usize];
+            spinloc[Spin::None as usize][to as usize][nx as usize] |= m ^ spins ^ minis;
+            spinloc[Spin::Mini as usize][to as usize][nx as usize] |= minis;
 
             let fullspins = fullspinmap[to as usize][nx as usize];
             spinloc[Spin::Mini as usize][to as usize][nx as usize] |= spins & !fullspins;
@@ -458,13 +480,15 @@ fn movegen_piece_o(arena: &mut Vec<PieceLocation>, board: &Board, force: bool) {
             s & s.wrapping_neg() // gets lowest bit. cursed overflow technique imo
         } else { !cm[SPAWN_COL] & bb(SPAWN_ROW) };
         
-        if spawn != 0 {
+        if spawn == 0 {
             return;
         }
 
         let spawn_bit = 1 << SPAWN_COL;
         remaining |= spawn_bit;
         to_search[SPAWN_COL] |= spawn;
+        // no bound when starting from spawn
+        max_moves = u32::MAX;
     } else {
         for x in 0..10 {
             let col = cm[x];
@@ -475,7 +499,7 @@ fn movegen_piece_o(arena: &mut Vec<PieceLocation>, board: &Board, force: bool) {
             let surface = bb_low(SPAWN_ROW) & !bb_low(y as i8);
 
             to_search[x] = surface & ((cm[x] << 1) | 1);
-            searched[x] |= surface;
+            searched[x] |= to_search[x];
             remaining |= 1 << x;
 
             arena.push(PieceLocation { piece: Piece::O, x: x as i8, y: y as i8, rotation: Rotation::North, spin: Spin::None });
@@ -532,50 +556,209 @@ fn movegen_piece_o(arena: &mut Vec<PieceLocation>, board: &Board, force: bool) {
     }
 }
 
-pub const fn kicks(piece: Piece, from: Rotation, to: Rotation) -> [(i8, i8); 5] {
-    match piece {
-        Piece::O => [(0, 0); 5], // just be careful not to rotate the O piece at all lol
-        Piece::I => match (from, to) {
-            (Rotation::East, Rotation::North) => [(-1, 0),(-2, 0),(1, 0),(-2, -2),(1, 1)],
-            (Rotation::East, Rotation::South) => [(0, -1),(-1, -1),(2, -1),(-1, 1),(2, -2)],
-            (Rotation::South, Rotation::East) => [(0, 1),(-2, 1),(1, 1),(-2, 2),(1, -1)],
-            (Rotation::South, Rotation::West) => [(-1, 0),(1, 0),(-2, 0),(1, 1),(-2, -2)],
-            (Rotation::West, Rotation::North) => [(0, 1),(1, 1),(-2, 1),(1, -1),(-2, 2)],
-            (Rotation::West, Rotation::South) => [(1, 0),(2, 0),(-1, 0),(2, 2),(-1, -1)],
-            (Rotation::North, Rotation::East) => [(1, 0),(2, 0),(-1, 0),(-1, -1),(2, 2)],
-            (Rotation::North, Rotation::West) => [(0, -1),(-1, -1),(2, -1),(2, -2),(-1, 1)],
-            _ => [(0, 0); 5] // this should never happen lol
-        },
-        _ => match (from, to) {
-            (Rotation::East, Rotation::North) => [(0, 0),(1, 0),(1, -1),(0, 2),(1, 2)],
-            (Rotation::East, Rotation::South) => [(0, 0),(1, 0),(1, -1),(0, 2),(1, 2)],
-            (Rotation::South, Rotation::East) => [(0, 0),(-1, 0),(-1, 1),(0, -2),(-1, -2)],
-            (Rotation::South, Rotation::West) => [(0, 0),(1, 0),(1, 1),(0, -2),(1, -2)],
-            (Rotation::West, Rotation::North) => [(0, 0),(-1, 0),(-1, -1),(0, 2),(-1, 2)],
-            (Rotation::West, Rotation::South) => [(0, 0),(-1, 0),(-1, -1),(0, 2),(-1, 2)],
-            (Rotation::North, Rotation::East) => [(0, 0),(-1, 0),(-1, 1),(0, -2),(-1, -2)],
-            (Rotation::North, Rotation::West) => [(0, 0),(1, 0),(1, 1),(0, -2),(1, -2)],
-            _ => [(0, 0); 5] // this should never happen lol
+// movegen for when pieces can't go everywhere the inputs allow (20g, no softdrop, limited move resets). the bitboard
+// search above assumes infinite softdrop and free inputs on the stack, so this is a bfs over single inputs instead, like
+// utils::reference but keeping track of how many resets each location costs. it is a lot slower.
+// resets are counted over the whole piece. the game gives them back when the piece reaches a new lowest row, so this can
+// miss placements that need more than the limit, but it never returns one the game wouldn't allow.
+fn movegen_piece_limited(arena: &mut Vec<PieceLocation>, board: &Board, piece: Piece, force: bool, config: &GameConfig) {
+    let Some(spawn) = spawn_location(board, piece, force) else { return };
+
+    let fall = |l: PieceLocation| if config.gravity == Gravity::Instant { sonic_drop(board, &l) } else { l };
+    let grounded = |l: &PieceLocation| board.collides(&PieceLocation { y: l.y - 1, ..l.clone() });
+    let resets = config.move_resets.unwrap_or(u8::MAX);
+    let key = |l: &PieceLocation| (l.x, l.y, l.rotation as u8, l.spin as u8);
+
+    // fewest resets used to get to each location. inputs in the air are free, so this is a 0-1 bfs
+    let mut used = HashMap::new();
+    let mut to_search = VecDeque::new();
+    let mut found: [[Board; 4]; 3] = std::array::from_fn(|_| std::array::from_fn(|_| Board::new()));
+
+    let start = fall(spawn);
+    used.insert(key(&start), 0);
+    to_search.push_back((start, 0));
+
+    while let Some((loc, n)) = to_search.pop_front() {
+        if used[&key(&loc)] < n {
+            continue;
+        }
+        let on_stack = grounded(&loc);
+        let lock = match config.gravity {
+            Gravity::HarddropOnly => Some(sonic_drop(board, &loc)),
+            _ => on_stack.then(|| loc.clone())
+        };
+        if let Some(lock) = lock {
+            let c = locked(board, &lock, config.spins);
+            found[c.spin as usize][c.rotation as usize][c.x as usize] |= bb(c.y);
+        }
+        if on_stack && n == resets {
+            continue;
+        }
+
+        let moves = [
+            try_shift(board, &loc, -1),
+            try_shift(board, &loc, 1),
+            try_rotate(board, &loc, loc.rotation.rotate_cw(), config),
+            try_rotate(board, &loc, loc.rotation.rotate_ccw(), config),
+            try_rotate(board, &loc, loc.rotation.rotate_180(), config),
+            (config.gravity == Gravity::Free && !on_stack).then(|| sonic_drop(board, &loc))
+        ];
+        let cost = on_stack as u8;
+        for next in moves.into_iter().flatten().map(fall) {
+            let entry = used.entry(key(&next)).or_insert(u8::MAX);
+            if n + cost < *entry {
+                *entry = n + cost;
+                match cost {
+                    0 => to_search.push_front((next, n)),
+                    _ => to_search.push_back((next, n + cost))
+                }
+            }
+        }
+    }
+
+    for spin in [Spin::None, Spin::Full, Spin::Mini] {
+        for rot in ROT {
+            for (x, &col) in found[spin as usize][rot as usize].cols.iter().enumerate() {
+                let mut m = col;
+                while m > 0 {
+                    arena.push(PieceLocation { piece, x: x as i8, y: m.trailing_zeros() as i8, rotation: rot, spin });
+                    m &= m - 1;
+                }
+            }
         }
     }
 }
 
-pub const fn kicks_180(piece: Piece, from: Rotation, to: Rotation) -> [(i8, i8); 6] {
-    match piece {
-        Piece::O => [(0, 0); 6], // just be careful not to rotate the O piece at all lol
-        Piece::I => match (from, to) {
-            (Rotation::East, Rotation::West) => [(-1, -1),(0, -1),(-1, -1),(-1, -1),(-1, -1),(-1, -1)],
-            (Rotation::South, Rotation::North) => [(-1, 1),(-1, 0),(-1, 1),(-1, 1),(-1, 1),(-1, 1)],
-            (Rotation::West, Rotation::East) => [(1, 1),(0, 1),(1, 1),(1, 1),(1, 1),(1, 1)],
-            (Rotation::North, Rotation::South) => [(1, -1),(1, 0),(1, -1),(1, -1),(1, -1),(1, -1)],
-            _ => [(0, 0); 6] // this should never happen lol
-        },
-        _ => match (from, to) {
-            (Rotation::East, Rotation::West) => [(0, 0),(1, 0),(1, 2),(1, 1),(0, 2),(0, 1)],
-            (Rotation::South, Rotation::North) => [(0, 0),(0, -1),(-1, -1),(1, -1),(-1, 0),(1, 0)],
-            (Rotation::West, Rotation::East) => [(0, 0),(-1, 0),(-1, 2),(-1, 1),(0, 2),(0, 1)],
-            (Rotation::North, Rotation::South) => [(0, 0),(0, 1),(1, 1),(-1, 1),(1, 0),(-1, 0)],
-            _ => [(0, 0); 6] // this should never happen lol
+// what a piece that locks at loc gets reported as: canonical, t spins as they were, O never spins, and any other piece is
+// labelled by whether it is immobile
+pub(crate) fn locked(board: &Board, loc: &PieceLocation, spins: SpinRule) -> PieceLocation {
+    let mut c = canonical(loc);
+    match loc.piece {
+        Piece::T => {}
+        Piece::O => c.spin = Spin::None,
+        _ => c.spin = rotation_spin(board, loc, false, spins)
+    }
+    c
+}
+
+// what each input costs when looking for the cheapest way to a placement
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct InputCost {
+    pub tap: u32,
+    // holding a direction until the piece hits something
+    pub das: u32,
+    pub rotate: u32,
+    // softdrop all the way down
+    pub softdrop: u32,
+    pub harddrop: u32
+}
+
+impl InputCost {
+    // every input is one keypress
+    pub const KEYS: Self = Self { tap: 1, das: 1, rotate: 1, softdrop: 1, harddrop: 1 };
+
+    // frames each input takes with 0 arr and instant softdrop, so only das takes longer than a frame
+    pub const fn frames(das: u32) -> Self {
+        Self { tap: 1, das, rotate: 1, softdrop: 1, harddrop: 1 }
+    }
+}
+
+// the cheapest input cost of reaching every placement (harddrop included). this is not the bitboard movegen: the bitboard
+// search only knows which cells are reachable, not how, so this is a separate dijkstra over single inputs (input::search)
+// that ends with the same placements as movegen_piece. it is a lot slower, so use it on the placements you are actually
+// going to play. with a reset limit, movegen_piece is run as well to drop what the limit makes unreachable, and the cost
+// is of the cheapest path ignoring the limit, which may need more resets than that
+pub fn placement_costs(arena: &mut Vec<(PieceLocation, u32)>, board: &Board, piece: Piece, force: bool, config: &GameConfig, cost: &InputCost) -> usize {
+    let idx = arena.len();
+    let paths = input::search(board, piece, force, config, |action, _| Some(match action {
+        MovementAction::TapLeft | MovementAction::TapRight => cost.tap,
+        MovementAction::DASLeft | MovementAction::DASRight => cost.das,
+        MovementAction::Softdrop => cost.softdrop,
+        MovementAction::Harddrop => cost.harddrop,
+        _ => cost.rotate
+    }));
+    let mut found: Vec<_> = paths.placements.into_values().map(|p| (p.loc, p.cost)).collect();
+    if config.move_resets.is_some() {
+        let mut reachable = vec![];
+        movegen_piece(&mut reachable, board, piece, force, config);
+        let reachable: HashSet<_> = reachable.iter().map(input::key).collect();
+        found.retain(|(l, _)| reachable.contains(&input::key(l)));
+    }
+    found.sort_by_key(|(l, _)| input::key(l));
+    arena.extend(found);
+    idx
+}
+
+// where the piece spawns. with force it goes up until it fits instead of failing when spawn is blocked
+pub fn spawn_location(board: &Board, piece: Piece, force: bool) -> Option<PieceLocation> {
+    let spawn = |y: i8| PieceLocation { piece, x: SPAWN_COL as i8, y, rotation: Rotation::North, spin: Spin::None };
+    if force {
+        (SPAWN_ROW..64).map(spawn).find(|l| !board.collides(l))
+    } else {
+        Some(spawn(SPAWN_ROW)).filter(|l| !board.collides(l))
+    }
+}
+
+// single step piece movement. the bfs above works on whole columns at once, these are for when inputs need to be replayed one at a time
+
+pub fn try_shift(board: &Board, loc: &PieceLocation, dx: i8) -> Option<PieceLocation> {
+    let new_loc = PieceLocation { x: loc.x + dx, spin: Spin::None, ..loc.clone() };
+    (!board.collides(&new_loc)).then_some(new_loc)
+}
+
+pub fn try_rotate(board: &Board, loc: &PieceLocation, to: Rotation, config: &GameConfig) -> Option<PieceLocation> {
+    let full_kick = config.rotation.full_spin_kick(loc.piece, loc.rotation, to);
+    for (i, (kx, ky)) in config.rotation.kicks(loc.piece, loc.rotation, to).enumerate() {
+        let new_loc = PieceLocation { piece: loc.piece, x: loc.x + kx, y: loc.y + ky, rotation: to, spin: Spin::None };
+        if !board.collides(&new_loc) {
+            let spin = rotation_spin(board, &new_loc, Some(i) == full_kick, config.spins);
+            return Some(PieceLocation { spin, ..new_loc });
         }
     }
-}
\ No newline at end of file
+    None
+}
+
+pub fn sonic_drop(board: &Board, loc: &PieceLocation) -> PieceLocation {
+    let mut new_loc = loc.clone();
+    while !board.collides(&PieceLocation { y: new_loc.y - 1, ..new_loc.clone() }) {
+        new_loc.y -= 1;
+        new_loc.spin = Spin::None;
+    }
+    new_loc
+}
+
+// spin of a piece that just rotated into loc. same rules as the bfs: 3 corner t-spins (full if both front corners
+// are filled or the last kick was used), and immobile pieces are whatever the spin rule makes them
+pub fn rotation_spin(board: &Board, loc: &PieceLocation, last_kick: bool, spins: SpinRule) -> Spin {
+    let immobile = || [(-1, 0), (1, 0), (0, 1), (0, -1)].into_iter()
+        .all(|(dx, dy)| board.collides(&PieceLocation { x: loc.x + dx, y: loc.y + dy, ..loc.clone() }));
+    if loc.piece != Piece::T {
+        return if immobile() { spins.immobile_spin() } else { Spin::None };
+    }
+
+    let filled = |dx: i8, dy: i8| {
+        let (x, y) = (loc.x + dx, loc.y + dy);
+        !(0..10).contains(&x) || y < 0 || board.cols[x as usize] & (1 << y) > 0
+    };
+    // same corner order as movegen_piece_t: top left, top right, bottom right, bottom left
+    let corners = [filled(-1, 1), filled(1, 1), filled(1, -1), filled(-1, -1)];
+    if corners.iter().filter(|&&c| c).count() < 3 {
+        return if spins == SpinRule::MiniPlus && immobile() { Spin::Mini } else { Spin::None };
+    }
+    let r = loc.rotation as usize;
+    if last_kick || (corners[r] && corners[(r + 1) & 3]) { Spin::Full } else { Spin::Mini }
+}
+
+// the same location the bitboard movegen would report: S/Z/I have two rotations covering the same cells, so South and West
+// are folded onto North and East
+pub fn canonical(loc: &PieceLocation) -> PieceLocation {
+    let (rotation, dx, dy) = match (loc.piece, loc.rotation) {
+        (Piece::O, _) => (Rotation::North, 0, 0),
+        (Piece::S | Piece::Z, Rotation::South) => (Rotation::North, 0, -1),
+        (Piece::S | Piece::Z, Rotation::West) => (Rotation::East, -1, 0),
+        (Piece::I, Rotation::South) => (Rotation::North, -1, 0),
+        (Piece::I, Rotation::West) => (Rotation::East, 0, 1),
+        (_, r) => (r, 0, 0)
+    };
+    PieceLocation { x: loc.x + dx, y: loc.y + dy, rotation, ..loc.clone() }
+}
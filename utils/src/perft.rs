@@ -0,0 +1,94 @@
+use crate::{
+    data::{Board, Piece, PieceLocation},
+    game::{Game, GameState},
+    movegen::movegen_piece,
+    reference::movegen_reference
+};
+
+// counts the leaves of the full placement tree, like chess perft. every location movegen reports is one node,
+// so a t-spin location reachable with two different spins counts twice.
+// with hold, every node also branches on the held piece (or the next piece if hold is empty, which uses up two pieces).
+// queue[0] is the current piece. the tree stops early where the queue runs out.
+pub fn perft(game: &Game, queue: &[Piece], depth: usize, hold: bool) -> usize {
+    let mut arena = vec![];
+    perft_inner(&mut arena, &game.state(), queue, depth, hold, &mut |_, _, _| Ok(())).unwrap()
+}
+
+#[derive(Debug, Clone)]
+pub struct PerftMismatch {
+    pub board: Board,
+    pub piece: Piece,
+    // found by the bitboard movegen but not the reference
+    pub extra: Vec<PieceLocation>,
+    // found by the reference but not the bitboard movegen
+    pub missing: Vec<PieceLocation>
+}
+
+// perft, but every movegen call is checked against the brute force reference. stops at the first position they disagree on
+pub fn perft_compare(game: &Game, queue: &[Piece], depth: usize, hold: bool) -> Result<usize, Box<PerftMismatch>> {
+    let mut arena = vec![];
+    let mut ref_arena = vec![];
+    perft_inner(&mut arena, &game.state(), queue, depth, hold, &mut |board, piece, found| {
+        ref_arena.clear();
+        movegen_reference(&mut ref_arena, board, piece, true, &game.config);
+        compare_locations(board, piece, found, &ref_arena)
+    })
+}
+
+pub fn compare_locations(board: &Board, piece: Piece, found: &[PieceLocation], reference: &[PieceLocation]) -> Result<(), Box<PerftMismatch>> {
+    let key = |l: &PieceLocation| (l.piece as u8, l.x, l.y, l.rotation as u8, l.spin as u8);
+    let mut a: Vec<_> = found.iter().map(key).collect();
+    let mut b: Vec<_> = reference.iter().map(key).collect();
+    a.sort();
+    b.sort();
+    if a == b {
+        return Ok(());
+    }
+    Err(Box::new(PerftMismatch {
+        board: *board,
+        piece,
+        extra: found.iter().filter(|l| !b.contains(&key(l))).cloned().collect(),
+        missing: reference.iter().filter(|l| !a.contains(&key(l))).cloned().collect()
+    }))
+}
+
+fn perft_inner(
+    arena: &mut Vec<PieceLocation>,
+    game: &GameState,
+    queue: &[Piece],
+    depth: usize,
+    hold: bool,
+    check: &mut impl FnMut(&Board, Piece, &[PieceLocation]) -> Result<(), Box<PerftMismatch>>
+) -> Result<usize, Box<PerftMismatch>> {
+    if depth == 0 || queue.is_empty() {
+        return Ok(1);
+    }
+
+    // (piece to place, pieces used up)
+    let mut branches = vec![(queue[0], 1)];
+    if hold {
+        match game.hold {
+            Some(h) if h != queue[0] => branches.push((h, 1)),
+            None if queue.len() > 1 => branches.push((queue[1], 2)),
+            _ => {}
+        }
+    }
+
+    let mut nodes = 0;
+    for (piece, used) in branches {
+        let start = movegen_piece(arena, &game.board, piece, true, game.config);
+        check(&game.board, piece, &arena[start..])?;
+        if depth == 1 {
+            nodes += arena.len() - start;
+            arena.truncate(start);
+            continue;
+        }
+
+        let moves: Vec<PieceLocation> = arena.drain(start..).collect();
+        for mv in moves {
+            let (next_game, _) = game.after(queue[0], &mv, 0);
+            nodes += perft_inner(arena, &next_game, &queue[used..], depth - 1, hold, check)?;
+        }
+    }
+    Ok(nodes)
+}
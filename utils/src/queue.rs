@@ -9,4 +9,56 @@ pub fn extend_queue(queue: &mut Vec<Piece>, bags: usize) {
         new_bag.shuffle(&mut rng);
         queue.extend(new_bag);
     }
-}
\ No newline at end of file
+}
+
+// park-miller lcg, same one tetr.io uses for its piece randomizer
+#[derive(Debug, Clone)]
+pub struct TetrioRng {
+    state: i64
+}
+
+impl TetrioRng {
+    pub fn new(seed: u64) -> Self {
+        let mut state = (seed % 2147483647) as i64;
+        if state <= 0 {
+            state += 2147483646;
+        }
+        Self { state }
+    }
+
+    pub fn next_int(&mut self) -> i64 {
+        self.state = 16807 * self.state % 2147483647;
+        self.state
+    }
+
+    pub fn next_float(&mut self) -> f64 {
+        (self.next_int() - 1) as f64 / 2147483646.0
+    }
+
+    pub fn shuffle<T>(&mut self, arr: &mut [T]) {
+        for i in (1..arr.len()).rev() {
+            let j = (self.next_float() * (i + 1) as f64) as usize;
+            arr.swap(i, j);
+        }
+    }
+}
+
+// reproduces the tetr.io 7-bag for a given seed, so replays and seeded benchmarks get the same queue every time
+#[derive(Debug, Clone)]
+pub struct SeededBag {
+    pub rng: TetrioRng
+}
+
+impl SeededBag {
+    pub fn new(seed: u64) -> Self {
+        Self { rng: TetrioRng::new(seed) }
+    }
+
+    pub fn extend(&mut self, queue: &mut Vec<Piece>, bags: usize) {
+        for _ in 0..bags {
+            let mut new_bag = [Piece::Z, Piece::L, Piece::O, Piece::S, Piece::I, Piece::J, Piece::T];
+            self.rng.shuffle(&mut new_bag);
+            queue.extend(new_bag);
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::{
+    data::{Board, Piece, PieceLocation, Rotation, Spin},
+    movegen::{canonical, try_shift, try_rotate, sonic_drop, SPAWN_COL, SPAWN_ROW},
+    game::GameConfig
+};
+
+// slow but obviously correct movegen: a plain bfs over every (x, y, rotation, spin) the piece can be in, starting from spawn
+// and trying every input (shifts, rotations, softdrop) one step at a time. it exists to check the bitboard movegen against,
+// so its output follows the same conventions: O is always North, I/S/Z are folded onto North/East, t spins come from the
+// last input (rotations only) so a t location can show up once per distinct spin, O never spins, and any other piece is a
+// immobile spin of the spin rule if it ends up immobile no matter how it got there.
+pub fn movegen_reference(arena: &mut Vec<PieceLocation>, board: &Board, piece: Piece, force: bool, config: &GameConfig) -> usize {
+    let idx = arena.len();
+    let spawn = |y: i8| PieceLocation { piece, x: SPAWN_COL as i8, y, rotation: Rotation::North, spin: Spin::None };
+    let spawn_y = if force {
+        (SPAWN_ROW..64).find(|&y| !board.collides(&spawn(y)))
+    } else {
+        Some(SPAWN_ROW).filter(|&y| !board.collides(&spawn(y)))
+    };
+    let Some(spawn_y) = spawn_y else { return idx };
+
+    let key = |l: &PieceLocation| (l.x, l.y, l.rotation as u8, l.spin as u8);
+    let mut seen = HashSet::new();
+    let mut to_search = VecDeque::new();
+    let mut found = HashSet::new();
+
+    seen.insert(key(&spawn(spawn_y)));
+    to_search.push_back(spawn(spawn_y));
+
+    while let Some(loc) = to_search.pop_front() {
+        if board.collides(&PieceLocation { y: loc.y - 1, ..loc.clone() }) {
+            let mut c = canonical(&loc);
+            match piece {
+                Piece::T => {}
+                Piece::O => c.spin = Spin::None,
+                _ => c.spin = if immobile(board, &loc) { config.spins.immobile_spin() } else { Spin::None }
+            }
+            found.insert((c.x, c.y, c.rotation as u8, c.spin as u8));
+        }
+
+        let moves = [
+            try_shift(board, &loc, -1),
+            try_shift(board, &loc, 1),
+            try_rotate(board, &loc, loc.rotation.rotate_cw(), config),
+            try_rotate(board, &loc, loc.rotation.rotate_ccw(), config),
+            try_rotate(board, &loc, loc.rotation.rotate_180(), config),
+            Some(sonic_drop(board, &loc))
+        ];
+        for next in moves.into_iter().flatten() {
+            if seen.insert(key(&next)) {
+                to_search.push_back(next);
+            }
+        }
+    }
+
+    let mut found: Vec<_> = found.into_iter().collect();
+    found.sort();
+    arena.extend(found.into_iter().map(|(x, y, r, s)| PieceLocation {
+        piece,
+        x,
+        y,
+        rotation: [Rotation::North, Rotation::East, Rotation::South, Rotation::West][r as usize],
+        spin: [Spin::None, Spin::Full, Spin::Mini][s as usize]
+    }));
+    idx
+}
+
+fn immobile(board: &Board, loc: &PieceLocation) -> bool {
+    [(-1, 0), (1, 0), (0, 1), (0, -1)].iter().all(|&(dx, dy)| board.collides(&PieceLocation { x: loc.x + dx, y: loc.y + dy, ..loc.clone() }))
+}
@@ -0,0 +1,511 @@
+use std::collections::VecDeque;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    data::{Piece, PieceLocation, Rotation, Spin},
+    game::{Game, GameConfig, PlacementInfo, SpinRule},
+    movegen::{try_shift, try_rotate, sonic_drop, SPAWN_COL, SPAWN_ROW},
+    queue::{SeededBag, TetrioRng},
+    rotation::{RotationSystem, Kicks180}
+};
+
+// tetr.io replays (.ttr for single player, .ttrm for multiplayer) are json files holding the raw key events of every player.
+// there is no board state in them, so positions are rebuilt by replaying the inputs on top of the seeded bag.
+// both the old layout (`data.events` / `data[].replays[]`) and the newer one (`replay.events` / `replay.rounds[][]`) are read.
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Json(serde_json::Error),
+    Format(String)
+}
+
+impl std::fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::Json(e) => write!(f, "invalid replay json: {e}"),
+            ReplayError::Format(s) => write!(f, "unrecognized replay format: {s}")
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<serde_json::Error> for ReplayError {
+    fn from(e: serde_json::Error) -> Self {
+        ReplayError::Json(e)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Key {
+    MoveLeft,
+    MoveRight,
+    SoftDrop,
+    HardDrop,
+    RotateCW,
+    RotateCCW,
+    Rotate180,
+    Hold
+}
+
+impl Key {
+    pub fn from_tetrio(key: &str) -> Option<Self> {
+        Some(match key {
+            "moveLeft" => Key::MoveLeft,
+            "moveRight" => Key::MoveRight,
+            "softDrop" => Key::SoftDrop,
+            "hardDrop" => Key::HardDrop,
+            "rotateCW" => Key::RotateCW,
+            "rotateCCW" => Key::RotateCCW,
+            "rotate180" => Key::Rotate180,
+            "hold" => Key::Hold,
+            _ => return None
+        })
+    }
+
+    pub const fn to_tetrio(&self) -> &'static str {
+        match self {
+            Key::MoveLeft => "moveLeft",
+            Key::MoveRight => "moveRight",
+            Key::SoftDrop => "softDrop",
+            Key::HardDrop => "hardDrop",
+            Key::RotateCW => "rotateCW",
+            Key::RotateCCW => "rotateCCW",
+            Key::Rotate180 => "rotate180",
+            Key::Hold => "hold"
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ReplayEvent {
+    KeyDown(Key),
+    KeyUp(Key),
+    // column is only present in older replays. without it the hole is picked by a seeded stand-in, see Simulator::lock
+    Garbage { amount: u16, column: Option<usize> }
+}
+
+#[derive(Debug, Clone)]
+pub struct TimedEvent {
+    pub frame: u32,
+    pub subframe: f64,
+    pub event: ReplayEvent
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplayOptions {
+    pub seed: u64,
+    pub das: f64,
+    pub arr: f64,
+    pub sdf: f64,
+    pub gravity: f64,
+    pub lock_delay: u32,
+    pub next_count: usize,
+    pub rotation: RotationSystem,
+    pub spins: SpinRule
+}
+
+#[derive(Debug, Clone)]
+pub struct PlayerReplay {
+    pub username: Option<String>,
+    pub round: usize,
+    pub options: ReplayOptions,
+    pub events: Vec<TimedEvent>
+}
+
+// one placement made by the player. game and queue are the state the piece spawned into (queue[0] is the current piece,
+// before any hold), so the pair can be fed straight into search and compared against location
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayPosition {
+    pub frame: u32,
+    pub game: Game,
+    pub queue: Vec<Piece>,
+    pub location: PieceLocation,
+    pub info: PlacementInfo
+}
+
+pub fn parse_replay(json: &str) -> Result<Vec<PlayerReplay>, ReplayError> {
+    let root: Value = serde_json::from_str(json)?;
+    let mut players = vec![];
+
+    if let Some(replay) = root.get("replay") {
+        if let Some(rounds) = replay.get("rounds").and_then(Value::as_array) {
+            for (round, entries) in rounds.iter().enumerate() {
+                let entries = entries.as_array().ok_or_else(|| format_err("round is not an array"))?;
+                for entry in entries {
+                    let inner = entry.get("replay").ok_or_else(|| format_err("round entry has no replay"))?;
+                    players.push(parse_player(inner, entry.get("username").and_then(Value::as_str), round)?);
+                }
+            }
+        } else {
+            let username = root.pointer("/users/0/username").and_then(Value::as_str);
+            players.push(parse_player(replay, username, 0)?);
+        }
+    } else if let Some(data) = root.get("data") {
+        match data {
+            Value::Array(rounds) => {
+                for (round, r) in rounds.iter().enumerate() {
+                    let replays = r.get("replays").and_then(Value::as_array).ok_or_else(|| format_err("round has no replays"))?;
+                    for replay in replays {
+                        players.push(parse_player(replay, None, round)?);
+                    }
+                }
+            }
+            Value::Object(_) => players.push(parse_player(data, None, 0)?),
+            _ => return Err(format_err("data is neither an object nor an array"))
+        }
+    } else {
+        return Err(format_err("no `replay` or `data` field"));
+    }
+
+    Ok(players)
+}
+
+fn format_err(s: &str) -> ReplayError {
+    ReplayError::Format(s.to_string())
+}
+
+fn parse_player(replay: &Value, username: Option<&str>, round: usize) -> Result<PlayerReplay, ReplayError> {
+    let raw_events = replay.get("events").and_then(Value::as_array).ok_or_else(|| format_err("replay has no events"))?;
+
+    // old replays keep their options in the first "full" event instead of next to the events
+    let options = replay.get("options")
+        .or_else(|| raw_events.iter()
+            .find(|e| e["type"] == "full")
+            .and_then(|e| e.pointer("/data/options")))
+        .ok_or_else(|| format_err("replay has no options"))?;
+
+    let num = |v: &Value, key: &str, default: f64| v.get(key).and_then(Value::as_f64).unwrap_or(default);
+    let handling = options.get("handling").unwrap_or(&Value::Null);
+    let parsed_options = ReplayOptions {
+        seed: options.get("seed").and_then(Value::as_f64).ok_or_else(|| format_err("options have no seed"))? as u64,
+        das: num(handling, "das", 10.0),
+        arr: num(handling, "arr", 2.0),
+        sdf: num(handling, "sdf", 6.0),
+        gravity: num(options, "g", 0.02),
+        lock_delay: num(options, "locktime", 30.0) as u32,
+        next_count: num(options, "nextcount", 5.0) as usize,
+        rotation: rotation_system(options),
+        spins: spin_rule(options)
+    };
+
+    let mut events = vec![];
+    for e in raw_events {
+        let frame = e.get("frame").and_then(Value::as_u64).unwrap_or(0) as u32;
+        let data = e.get("data").unwrap_or(&Value::Null);
+        let subframe = num(data, "subframe", 0.0);
+        let event = match e["type"].as_str() {
+            Some("keydown") => data["key"].as_str().and_then(Key::from_tetrio).map(ReplayEvent::KeyDown),
+            Some("keyup") => data["key"].as_str().and_then(Key::from_tetrio).map(ReplayEvent::KeyUp),
+            Some("ige") => parse_garbage(data),
+            _ => None
+        };
+        if let Some(event) = event {
+            events.push(TimedEvent { frame, subframe, event });
+        }
+    }
+    events.sort_by(|a, b| (a.frame, a.subframe).partial_cmp(&(b.frame, b.subframe)).unwrap());
+
+    Ok(PlayerReplay {
+        username: username.or_else(|| options.get("username").and_then(Value::as_str)).map(str::to_string),
+        round,
+        options: parsed_options,
+        events
+    })
+}
+
+fn parse_garbage(data: &Value) -> Option<ReplayEvent> {
+    let inner = data.get("data")?;
+    let (amount, column) = match (data["type"].as_str()?, inner["type"].as_str()?) {
+        // old: { type: "ige", data: { type: "attack", lines, column } }
+        (_, "attack") => (inner.get("lines")?, inner.get("column")),
+        // new: { type: "interaction_confirm", data: { type: "garbage", amount } }. the unconfirmed "interaction" is skipped so nothing is counted twice
+        ("interaction_confirm", "garbage") => (inner.get("amount")?, inner.get("column")),
+        _ => return None
+    };
+    Some(ReplayEvent::Garbage {
+        amount: amount.as_u64()? as u16,
+        column: column.and_then(Value::as_u64).map(|c| c as usize).filter(|&c| c < 10)
+    })
+}
+
+impl PlayerReplay {
+    // replays the key events and returns every placement in order. the simulation is input accurate rather than frame accurate:
+    // das/arr/sdf, gravity and lock delay are modelled per frame, but subframes only decide event order and move resets are unlimited.
+    pub fn simulate(&self) -> Vec<ReplayPosition> {
+        let mut sim = Simulator::new(&self.options);
+        let mut events = self.events.iter().peekable();
+        let mut frame = 0;
+        while events.peek().is_some() && !sim.dead {
+            while let Some(e) = events.next_if(|e| e.frame <= frame) {
+                sim.handle(e.event, frame);
+            }
+            sim.tick(frame);
+            frame += 1;
+        }
+        sim.positions
+    }
+}
+
+// tetr.io's kickset and allow180 options. kicksets we don't have a table for fall back to srs+
+fn rotation_system(options: &Value) -> RotationSystem {
+    let mut rs = match options.get("kickset").and_then(Value::as_str) {
+        // tetr.io's srs has no 180 table either, so it's the guideline preset
+        Some("SRS") => RotationSystem::SRS,
+        Some("SRS-X") => RotationSystem::SRS_X,
+        Some("none") => RotationSystem::NO_KICK,
+        _ => RotationSystem::SRS_PLUS
+    };
+    if options.get("allow180").and_then(Value::as_bool) == Some(false) {
+        rs.kicks_180 = Kicks180::Disabled;
+    }
+    rs
+}
+
+// tetr.io's spinbonuses option. the modes we don't model (stupid, handheld, none) fall back to the closest one
+fn spin_rule(options: &Value) -> SpinRule {
+    match options.get("spinbonuses").and_then(Value::as_str) {
+        Some("T-spins") | Some("none") => SpinRule::TSpins,
+        Some("all") | Some("stupid") => SpinRule::AllSpin,
+        Some("all-mini+") => SpinRule::MiniPlus,
+        _ => SpinRule::AllMini
+    }
+}
+
+struct Simulator<'a> {
+    options: &'a ReplayOptions,
+    game: Game,
+    bag: SeededBag,
+    queue: Vec<Piece>,
+    garbage_rng: TetrioRng,
+    garbage_cols: VecDeque<(u16, Option<usize>)>,
+    active: Option<PieceLocation>,
+    spawned: Option<(Game, Vec<Piece>)>,
+    // mirrors game.hold while a piece is falling, Game::advance only swaps the real one once the piece locks
+    hold: Option<Piece>,
+    hold_used: bool,
+    left_held: bool,
+    right_held: bool,
+    soft_held: bool,
+    dir: i8,
+    das_timer: f64,
+    arr_timer: f64,
+    fall: f64,
+    lock_timer: u32,
+    dead: bool,
+    positions: Vec<ReplayPosition>
+}
+
+impl<'a> Simulator<'a> {
+    fn new(options: &'a ReplayOptions) -> Self {
+        let mut sim = Self {
+            options,
+            game: Game { config: GameConfig { rotation: options.rotation, spins: options.spins, ..Default::default() }, ..Game::new_empty() },
+            bag: SeededBag::new(options.seed),
+            queue: vec![],
+            garbage_rng: TetrioRng::new(options.seed),
+            garbage_cols: VecDeque::new(),
+            active: None,
+            spawned: None,
+            hold: None,
+            hold_used: false,
+            left_held: false,
+            right_held: false,
+            soft_held: false,
+            dir: 0,
+            das_timer: 0.0,
+            arr_timer: 0.0,
+            fall: 0.0,
+            lock_timer: 0,
+            dead: false,
+            positions: vec![]
+        };
+        sim.spawn_next();
+        sim
+    }
+
+    fn take_piece(&mut self) -> Piece {
+        while self.queue.len() <= self.options.next_count {
+            self.bag.extend(&mut self.queue, 1);
+        }
+        self.queue.remove(0)
+    }
+
+    fn spawn_next(&mut self) {
+        let piece = self.take_piece();
+        let mut queue = vec![piece];
+        queue.extend_from_slice(&self.queue[..self.options.next_count]);
+        self.spawned = Some((self.game.clone(), queue));
+        self.hold = self.game.hold;
+        self.hold_used = false;
+        self.spawn(piece);
+    }
+
+    fn spawn(&mut self, piece: Piece) {
+        let loc = PieceLocation { piece, x: SPAWN_COL as i8, y: SPAWN_ROW, rotation: Rotation::North, spin: Spin::None };
+        if self.game.board.collides(&loc) {
+            self.dead = true;
+            self.active = None;
+            return;
+        }
+        self.active = Some(loc);
+        self.fall = 0.0;
+        self.lock_timer = 0;
+        // a das charged before the piece spawned carries over
+        if self.dir != 0 && self.das_timer >= self.options.das && self.options.arr == 0.0 {
+            self.shift_to_wall();
+        }
+    }
+
+    fn shift(&mut self, dx: i8) -> bool {
+        let Some(loc) = &self.active else { return false };
+        match try_shift(&self.game.board, loc, dx) {
+            Some(new_loc) => {
+                self.active = Some(new_loc);
+                self.lock_timer = 0;
+                true
+            }
+            None => false
+        }
+    }
+
+    fn shift_to_wall(&mut self) {
+        while self.shift(self.dir) {}
+    }
+
+    fn rotate(&mut self, to: fn(&Rotation) -> Rotation) {
+        let Some(loc) = &self.active else { return };
+        if let Some(new_loc) = try_rotate(&self.game.board, loc, to(&loc.rotation), &self.game.config) {
+            self.active = Some(new_loc);
+            self.lock_timer = 0;
+        }
+    }
+
+    fn hold(&mut self) {
+        let Some(active) = &self.active else { return };
+        if self.hold_used {
+            return;
+        }
+        self.hold_used = true;
+        let piece = match self.hold.replace(active.piece) {
+            Some(h) => h,
+            None => self.take_piece()
+        };
+        self.spawn(piece);
+    }
+
+    fn handle(&mut self, event: ReplayEvent, frame: u32) {
+        match event {
+            ReplayEvent::KeyDown(Key::MoveLeft) | ReplayEvent::KeyDown(Key::MoveRight) => {
+                let dx = if event == ReplayEvent::KeyDown(Key::MoveLeft) { -1 } else { 1 };
+                if dx < 0 { self.left_held = true } else { self.right_held = true }
+                self.dir = dx;
+                self.das_timer = 0.0;
+                self.arr_timer = 0.0;
+                self.shift(dx);
+            }
+            ReplayEvent::KeyUp(Key::MoveLeft) | ReplayEvent::KeyUp(Key::MoveRight) => {
+                let dx = if event == ReplayEvent::KeyUp(Key::MoveLeft) { -1 } else { 1 };
+                if dx < 0 { self.left_held = false } else { self.right_held = false }
+                if self.dir == dx {
+                    self.dir = if self.left_held { -1 } else if self.right_held { 1 } else { 0 };
+                    self.das_timer = 0.0;
+                    self.arr_timer = 0.0;
+                }
+            }
+            ReplayEvent::KeyDown(Key::SoftDrop) => self.soft_held = true,
+            ReplayEvent::KeyUp(Key::SoftDrop) => self.soft_held = false,
+            ReplayEvent::KeyDown(Key::HardDrop) => {
+                if let Some(loc) = &self.active {
+                    self.active = Some(sonic_drop(&self.game.board, loc));
+                    self.lock(frame);
+                }
+            }
+            ReplayEvent::KeyDown(Key::RotateCW) => self.rotate(Rotation::rotate_cw),
+            ReplayEvent::KeyDown(Key::RotateCCW) => self.rotate(Rotation::rotate_ccw),
+            ReplayEvent::KeyDown(Key::Rotate180) => self.rotate(Rotation::rotate_180),
+            ReplayEvent::KeyDown(Key::Hold) => self.hold(),
+            ReplayEvent::Garbage { amount, column } => {
+                self.game.incoming_garbage += amount;
+                self.garbage_cols.push_back((amount, column));
+            }
+            ReplayEvent::KeyUp(_) => {}
+        }
+    }
+
+    fn tick(&mut self, frame: u32) {
+        if self.active.is_none() {
+            return;
+        }
+
+        if self.dir != 0 {
+            self.das_timer += 1.0;
+            if self.das_timer >= self.options.das {
+                if self.options.arr == 0.0 {
+                    self.shift_to_wall();
+                } else {
+                    self.arr_timer += 1.0;
+                    while self.arr_timer >= self.options.arr {
+                        self.arr_timer -= self.options.arr;
+                        self.shift(self.dir);
+                    }
+                }
+            }
+        }
+
+        self.fall += match self.soft_held {
+            true if self.options.sdf >= 41.0 => f64::INFINITY,
+            true => self.options.gravity.max(0.05) * self.options.sdf,
+            false => self.options.gravity
+        };
+        let loc = self.active.clone().unwrap();
+        let mut dropped = loc.clone();
+        while self.fall >= 1.0 {
+            let below = PieceLocation { y: dropped.y - 1, spin: Spin::None, ..dropped.clone() };
+            if self.game.board.collides(&below) {
+                self.fall = 0.0;
+                break;
+            }
+            dropped = below;
+            self.fall -= 1.0;
+        }
+        if dropped.y != loc.y {
+            self.lock_timer = 0;
+        }
+        self.active = Some(dropped.clone());
+
+        if self.game.board.collides(&PieceLocation { y: dropped.y - 1, ..dropped }) {
+            self.lock_timer += 1;
+            if self.lock_timer >= self.options.lock_delay {
+                self.lock(frame);
+            }
+        } else {
+            self.lock_timer = 0;
+        }
+    }
+
+    fn lock(&mut self, frame: u32) {
+        let Some(location) = self.active.take() else { return };
+        let (game, queue) = self.spawned.take().unwrap();
+
+        let before = self.game.incoming_garbage;
+        let column = self.garbage_cols.front().and_then(|&(_, c)| c);
+        let rng = &mut self.garbage_rng;
+        let info = self.game.advance_with(queue[0], &location, || column.unwrap_or_else(|| (rng.next_float() * 10.0) as usize));
+
+        // drop the garbage chunks that were cancelled or tanked by this placement
+        let mut consumed = before - self.game.incoming_garbage;
+        while consumed > 0 && let Some(front) = self.garbage_cols.front_mut() {
+            let n = front.0.min(consumed);
+            front.0 -= n;
+            consumed -= n;
+            if front.0 == 0 {
+                self.garbage_cols.pop_front();
+            }
+        }
+
+        self.positions.push(ReplayPosition { frame, game, queue, location, info });
+        self.spawn_next();
+    }
+}
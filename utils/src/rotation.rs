@@ -0,0 +1,187 @@
+use serde::{Serialize, Deserialize};
+
+use crate::data::{Piece, Rotation, box_offset};
+
+// kick tables for the supported rotation systems. tables are written the way the srs guideline (and tetris wiki) writes them:
+// offsets are tried in order, y points up, and the piece rotates around its true srs center. internally, pieces rotate
+// around the center block instead, which is the same thing for everything except I. rotating an I around its center block
+// ends up one cell off in some direction, so that difference gets added on every I kick (see data::box_offset).
+//
+// a rotation system is a 90 degree table plus a 180 degree table, so things like "srs+ without 180s" can be picked freely.
+// the presets below are the usual combinations.
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Kicks {
+    // guideline srs
+    Srs,
+    // tetr.io srs+: srs with symmetric I kicks
+    #[default]
+    SrsPlus,
+    // arika style: rotations keep pieces sitting on the same floor, and only kick one cell right or left. I never kicks.
+    // spawn orientation stays the same as everything else (flat side down), only the rotation behaviour is different
+    Ars,
+    // rotate in place or not at all
+    None
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Kicks180 {
+    // tetr.io srs+ 180 kicks
+    #[default]
+    Tetrio,
+    // tetr.io srs-x, the heboris 180 table
+    SrsX,
+    // 180s are allowed but never kick
+    InPlace,
+    // no 180 rotation at all
+    Disabled
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct RotationSystem {
+    pub kicks: Kicks,
+    pub kicks_180: Kicks180
+}
+
+impl RotationSystem {
+    pub const SRS: Self = Self { kicks: Kicks::Srs, kicks_180: Kicks180::Disabled };
+    pub const SRS_PLUS: Self = Self { kicks: Kicks::SrsPlus, kicks_180: Kicks180::Tetrio };
+    pub const SRS_X: Self = Self { kicks: Kicks::Srs, kicks_180: Kicks180::SrsX };
+    pub const ARS: Self = Self { kicks: Kicks::Ars, kicks_180: Kicks180::Disabled };
+    pub const NO_KICK: Self = Self { kicks: Kicks::None, kicks_180: Kicks180::InPlace };
+
+    // offsets to try, in order, when rotating from `from` to `to`. empty if the rotation isn't possible at all (O pieces,
+    // rotating to the same rotation, or a disabled 180)
+    pub fn kicks(&self, piece: Piece, from: Rotation, to: Rotation) -> impl Iterator<Item = (i8, i8)> + use<> {
+        let table = if piece == Piece::O || from == to {
+            &[]
+        } else if to == from.rotate_180() {
+            kicks_180(self.kicks_180, piece, from, to)
+        } else {
+            kicks(self.kicks, piece, from, to)
+        };
+        let (dx, dy) = if piece == Piece::I {
+            let (fx, fy) = box_offset(piece, from);
+            let (tx, ty) = box_offset(piece, to);
+            (tx - fx, ty - fy)
+        } else { (0, 0) };
+        table.iter().map(move |&(kx, ky)| (kx + dx, ky + dy))
+    }
+
+    // which kick makes any 3 corner t-spin full (the last srs kick, used by tst and fin setups). 180s and tables without
+    // such a kick have none
+    pub fn full_spin_kick(&self, piece: Piece, from: Rotation, to: Rotation) -> Option<usize> {
+        if piece == Piece::O || from == to || to == from.rotate_180() {
+            return None;
+        }
+        match self.kicks {
+            Kicks::Srs | Kicks::SrsPlus => kicks(self.kicks, piece, from, to).len().checked_sub(1),
+            Kicks::Ars | Kicks::None => None
+        }
+    }
+
+    pub fn can_rotate_180(&self) -> bool {
+        self.kicks_180 != Kicks180::Disabled
+    }
+}
+
+const fn kicks(kicks: Kicks, piece: Piece, from: Rotation, to: Rotation) -> &'static [(i8, i8)] {
+    match (kicks, piece) {
+        (Kicks::None, _) => &[(0, 0)],
+        (Kicks::Ars, _) => ars_kicks(piece, from, to),
+        (Kicks::Srs, Piece::I) => match (from, to) {
+            (Rotation::North, Rotation::East) => &[(0, 0),(-2, 0),(1, 0),(-2, -1),(1, 2)],
+            (Rotation::East, Rotation::North) => &[(0, 0),(2, 0),(-1, 0),(2, 1),(-1, -2)],
+            (Rotation::East, Rotation::South) => &[(0, 0),(-1, 0),(2, 0),(-1, 2),(2, -1)],
+            (Rotation::South, Rotation::East) => &[(0, 0),(1, 0),(-2, 0),(1, -2),(-2, 1)],
+            (Rotation::South, Rotation::West) => &[(0, 0),(2, 0),(-1, 0),(2, 1),(-1, -2)],
+            (Rotation::West, Rotation::South) => &[(0, 0),(-2, 0),(1, 0),(-2, -1),(1, 2)],
+            (Rotation::West, Rotation::North) => &[(0, 0),(1, 0),(-2, 0),(1, -2),(-2, 1)],
+            (Rotation::North, Rotation::West) => &[(0, 0),(-1, 0),(2, 0),(-1, 2),(2, -1)],
+            _ => &[]
+        },
+        (Kicks::SrsPlus, Piece::I) => match (from, to) {
+            (Rotation::North, Rotation::East) => &[(0, 0),(1, 0),(-2, 0),(-2, -1),(1, 2)],
+            (Rotation::East, Rotation::North) => &[(0, 0),(-1, 0),(2, 0),(-1, -2),(2, 1)],
+            (Rotation::East, Rotation::South) => &[(0, 0),(-1, 0),(2, 0),(-1, 2),(2, -1)],
+            (Rotation::South, Rotation::East) => &[(0, 0),(-2, 0),(1, 0),(-2, 1),(1, -2)],
+            (Rotation::South, Rotation::West) => &[(0, 0),(2, 0),(-1, 0),(2, 1),(-1, -2)],
+            (Rotation::West, Rotation::South) => &[(0, 0),(1, 0),(-2, 0),(1, 2),(-2, -1)],
+            (Rotation::West, Rotation::North) => &[(0, 0),(1, 0),(-2, 0),(1, -2),(-2, 1)],
+            (Rotation::North, Rotation::West) => &[(0, 0),(-1, 0),(2, 0),(2, -1),(-1, 2)],
+            _ => &[]
+        },
+        // J L S T Z are the same in srs and srs+
+        (Kicks::Srs | Kicks::SrsPlus, _) => match (from, to) {
+            (Rotation::North, Rotation::East) => &[(0, 0),(-1, 0),(-1, 1),(0, -2),(-1, -2)],
+            (Rotation::East, Rotation::North) => &[(0, 0),(1, 0),(1, -1),(0, 2),(1, 2)],
+            (Rotation::East, Rotation::South) => &[(0, 0),(1, 0),(1, -1),(0, 2),(1, 2)],
+            (Rotation::South, Rotation::East) => &[(0, 0),(-1, 0),(-1, 1),(0, -2),(-1, -2)],
+            (Rotation::South, Rotation::West) => &[(0, 0),(1, 0),(1, 1),(0, -2),(1, -2)],
+            (Rotation::West, Rotation::South) => &[(0, 0),(-1, 0),(-1, -1),(0, 2),(-1, 2)],
+            (Rotation::West, Rotation::North) => &[(0, 0),(-1, 0),(-1, -1),(0, 2),(-1, 2)],
+            (Rotation::North, Rotation::West) => &[(0, 0),(1, 0),(1, 1),(0, -2),(1, -2)],
+            _ => &[]
+        }
+    }
+}
+
+// ars rotates pieces so they keep resting on the same row (and vertical S/Z/I keep the same column), which is a fixed offset
+// from the srs position of each state. the kick is the difference between the two states' offsets, then one to the right,
+// then one to the left
+const fn ars_kicks(piece: Piece, from: Rotation, to: Rotation) -> &'static [(i8, i8)] {
+    match (piece, from, to) {
+        (Piece::I, Rotation::North, Rotation::East) | (Piece::I, Rotation::East, Rotation::North) => &[(0, 0)],
+        (Piece::I, Rotation::North, Rotation::West) => &[(1, 0)],
+        (Piece::I, Rotation::West, Rotation::North) => &[(-1, 0)],
+        (Piece::I, Rotation::South, Rotation::East) => &[(0, -1)],
+        (Piece::I, Rotation::East, Rotation::South) => &[(0, 1)],
+        (Piece::I, Rotation::South, Rotation::West) => &[(1, -1)],
+        (Piece::I, Rotation::West, Rotation::South) => &[(-1, 1)],
+        (Piece::S | Piece::Z, Rotation::North, Rotation::East) => &[(-1, 1),(0, 1),(-2, 1)],
+        (Piece::S | Piece::Z, Rotation::East, Rotation::North) => &[(1, -1),(2, -1),(0, -1)],
+        (Piece::S | Piece::Z, Rotation::North, Rotation::West) => &[(0, 1),(1, 1),(-1, 1)],
+        (Piece::S | Piece::Z, Rotation::West, Rotation::North) => &[(0, -1),(1, -1),(-1, -1)],
+        (Piece::S | Piece::Z, Rotation::South, Rotation::East) => &[(-1, 0),(0, 0),(-2, 0)],
+        (Piece::S | Piece::Z, Rotation::East, Rotation::South) => &[(1, 0),(2, 0),(0, 0)],
+        (Piece::S | Piece::Z, Rotation::South | Rotation::West, _) => &[(0, 0),(1, 0),(-1, 0)],
+        (_, Rotation::North, _) => &[(0, 1),(1, 1),(-1, 1)],
+        (_, _, Rotation::North) => &[(0, -1),(1, -1),(-1, -1)],
+        _ => &[(0, 0),(1, 0),(-1, 0)]
+    }
+}
+
+const fn kicks_180(kicks: Kicks180, piece: Piece, from: Rotation, to: Rotation) -> &'static [(i8, i8)] {
+    match (kicks, piece) {
+        (Kicks180::Disabled, _) => &[],
+        (Kicks180::InPlace, _) => &[(0, 0)],
+        (Kicks180::Tetrio, Piece::I) => match (from, to) {
+            (Rotation::North, Rotation::South) => &[(0, 0),(0, 1)],
+            (Rotation::East, Rotation::West) => &[(0, 0),(1, 0)],
+            (Rotation::South, Rotation::North) => &[(0, 0),(0, -1)],
+            (Rotation::West, Rotation::East) => &[(0, 0),(-1, 0)],
+            _ => &[]
+        },
+        (Kicks180::Tetrio, _) => match (from, to) {
+            (Rotation::North, Rotation::South) => &[(0, 0),(0, 1),(1, 1),(-1, 1),(1, 0),(-1, 0)],
+            (Rotation::East, Rotation::West) => &[(0, 0),(1, 0),(1, 2),(1, 1),(0, 2),(0, 1)],
+            (Rotation::South, Rotation::North) => &[(0, 0),(0, -1),(-1, -1),(1, -1),(-1, 0),(1, 0)],
+            (Rotation::West, Rotation::East) => &[(0, 0),(-1, 0),(-1, 2),(-1, 1),(0, 2),(0, 1)],
+            _ => &[]
+        },
+        (Kicks180::SrsX, Piece::I) => match (from, to) {
+            (Rotation::North, Rotation::South) => &[(0, 0),(-1, 0),(-2, 0),(1, 0),(2, 0),(0, 1)],
+            (Rotation::East, Rotation::West) => &[(0, 0),(0, 1),(0, 2),(0, -1),(0, -2),(-1, 0)],
+            (Rotation::South, Rotation::North) => &[(0, 0),(1, 0),(2, 0),(-1, 0),(-2, 0),(0, -1)],
+            (Rotation::West, Rotation::East) => &[(0, 0),(0, 1),(0, 2),(0, -1),(0, -2),(1, 0)],
+            _ => &[]
+        },
+        (Kicks180::SrsX, _) => match (from, to) {
+            (Rotation::North, Rotation::South) => &[(0, 0),(1, 0),(2, 0),(1, 1),(2, 1),(-1, 0),(-2, 0),(-1, 1),(-2, 1),(0, -1),(3, 0),(-3, 0)],
+            (Rotation::East, Rotation::West) => &[(0, 0),(0, 1),(0, 2),(-1, 1),(-1, 2),(0, -1),(0, -2),(-1, -1),(-1, -2),(1, 0),(0, 3),(0, -3)],
+            (Rotation::South, Rotation::North) => &[(0, 0),(-1, 0),(-2, 0),(-1, -1),(-2, -1),(1, 0),(2, 0),(1, -1),(2, -1),(0, 1),(-3, 0),(3, 0)],
+            (Rotation::West, Rotation::East) => &[(0, 0),(0, 1),(0, 2),(1, 1),(1, 2),(0, -1),(0, -2),(1, -1),(1, -2),(-1, 0),(0, 3),(0, -3)],
+            _ => &[]
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use utils::cheese::Cheese;
+
+fn holes(messiness: f64, seed: u64) -> Vec<usize> {
+    let mut cheese = Cheese::new(seed, messiness);
+    (0..50).map(|_| cheese.next_hole()).collect()
+}
+
+#[test]
+fn messiness() {
+    for seed in 0..10 {
+        let clean = holes(0.0, seed);
+        assert!(clean.iter().all(|&h| h == clean[0]));
+        let messy = holes(1.0, seed);
+        assert!(messy.windows(2).all(|w| w[0] != w[1]));
+        assert!(messy.iter().all(|&h| h < 10));
+        // somewhere in between both happen
+        let half = holes(0.5, seed);
+        assert!(half.windows(2).any(|w| w[0] == w[1]) && half.windows(2).any(|w| w[0] != w[1]));
+    }
+    assert_eq!(holes(0.3, 7), holes(0.3, 7));
+    assert_ne!(holes(1.0, 7), holes(1.0, 8));
+}
+
+#[test]
+fn board() {
+    let mut cheese = Cheese::new(3, 1.0);
+    let board = cheese.board(8);
+    assert_eq!(board.max_height(), 8);
+    for y in 0..8 {
+        let row: Vec<usize> = (0..10).filter(|&x| board.cols[x] & (1 << y) == 0).collect();
+        assert_eq!(row.len(), 1, "row {y}");
+    }
+
+    // new lines go under the old ones
+    let mut again = Cheese::new(3, 1.0);
+    let mut board = again.board(4);
+    let top = board.cols;
+    again.add_lines(&mut board, 4);
+    assert!((0..10).all(|x| board.cols[x] >> 4 == top[x]));
+}
@@ -0,0 +1,55 @@
+// fixtures shared by the test files. every file under tests/ is its own crate and only uses some of these
+#![allow(dead_code)]
+
+use bitboard_traits::BitboardTrait;
+use rand::{Rng, rngs::StdRng};
+use utils::{
+    data::{Board, Piece, PieceLocation, Rotation, Spin},
+    game::{GameConfig, Gravity, SpinRule},
+    movegen::movegen_piece,
+    rotation::RotationSystem
+};
+
+pub const PIECES: [Piece; 7] = [Piece::I, Piece::O, Piece::T, Piece::L, Piece::J, Piece::S, Piece::Z];
+
+pub fn loc(piece: Piece, x: i8, y: i8, rotation: Rotation) -> PieceLocation {
+    PieceLocation { piece, x, y, rotation, spin: Spin::None }
+}
+
+pub fn with_gravity(gravity: Gravity, move_resets: Option<u8>) -> GameConfig {
+    GameConfig { gravity, move_resets, ..Default::default() }
+}
+
+pub fn with_rotation(rotation: RotationSystem) -> GameConfig {
+    GameConfig { rotation, ..Default::default() }
+}
+
+pub fn with_spins(spins: SpinRule) -> GameConfig {
+    GameConfig { spins, ..Default::default() }
+}
+
+pub fn placements(board: &Board, piece: Piece, config: &GameConfig) -> Vec<PieceLocation> {
+    let mut arena = vec![];
+    movegen_piece(&mut arena, board, piece, true, config);
+    arena
+}
+
+// independent random cells up to height, filled with chance density at the bottom and less further up. lots of holes,
+// overhangs and spin setups
+pub fn noise(rng: &mut StdRng, height: usize, density: f64) -> Board {
+    let mut board = Board::new();
+    for x in 0..10 {
+        for y in 0..height {
+            if rng.random_bool(density - 0.5 * y as f64 / height as f64) {
+                board.cols[x] |= 1 << y;
+            }
+        }
+    }
+    board
+}
+
+// a low noisy stack, 2 to 9 rows
+pub fn stack(rng: &mut StdRng) -> Board {
+    let height = rng.random_range(2..10);
+    noise(rng, height, 0.7)
+}
@@ -0,0 +1,104 @@
+use utils::{
+    data::{Board, Piece, PieceLocation, Rotation, ROT},
+    game::GameConfig,
+    movegen::try_rotate,
+    rotation::RotationSystem
+};
+
+mod common;
+use common::{PIECES, loc};
+
+fn sorted(mut cells: Vec<(i8, i8)>) -> Vec<(i8, i8)> {
+    cells.sort();
+    cells
+}
+
+// guideline srs shapes for north, as (column, row) in the bounding box with rows going down
+fn srs_box(piece: Piece) -> (i8, [(i8, i8); 4]) {
+    match piece {
+        Piece::I => (4, [(0, 1), (1, 1), (2, 1), (3, 1)]),
+        Piece::O => (2, [(0, 0), (1, 0), (0, 1), (1, 1)]),
+        Piece::T => (3, [(1, 0), (0, 1), (1, 1), (2, 1)]),
+        Piece::J => (3, [(0, 0), (0, 1), (1, 1), (2, 1)]),
+        Piece::L => (3, [(2, 0), (0, 1), (1, 1), (2, 1)]),
+        Piece::S => (3, [(1, 0), (2, 0), (0, 1), (1, 1)]),
+        Piece::Z => (3, [(0, 0), (1, 0), (1, 1), (2, 1)])
+    }
+}
+
+// cells of a piece whose srs box has its top left at (x, y), rotating the box the way the guideline does
+fn srs_cells(piece: Piece, rotation: Rotation, x: i8, y: i8) -> Vec<(i8, i8)> {
+    let (n, mut cells) = srs_box(piece);
+    for _ in 0..rotation as usize {
+        cells = cells.map(|(c, r)| (n - 1 - r, c));
+    }
+    sorted(cells.iter().map(|&(c, r)| (x + c, y - r)).collect())
+}
+
+#[test]
+fn round_trips() {
+    for piece in PIECES {
+        for rotation in ROT {
+            for (x, y) in [(0, 0), (4, 21), (-3, 7), (9, 39)] {
+                let l = loc(piece, x, y, rotation);
+                let (tx, ty) = l.to_tetrio();
+                let (sx, sy) = l.to_srs();
+                let (fx, fy) = l.to_fumen();
+                assert_eq!(PieceLocation::from_tetrio(piece, rotation, tx, ty).blocks(), l.blocks());
+                assert_eq!(PieceLocation::from_srs(piece, rotation, sx, sy).blocks(), l.blocks());
+                assert_eq!(PieceLocation::from_fumen(piece, rotation, fx, fy).blocks(), l.blocks());
+            }
+        }
+    }
+}
+
+// the offsets play.js used to go from our position to the tetr.io corner, indexed north, east, south, west
+#[test]
+fn tetrio_matches_client() {
+    let i = [(-1, 1), (-2, 1), (-2, 2), (-1, 2)];
+    let o = [(0, 1), (0, 0), (-1, 0), (-1, 1)];
+    for piece in PIECES {
+        for (r, rotation) in ROT.into_iter().enumerate() {
+            let expected = match piece {
+                Piece::I => i[r],
+                Piece::O => o[r],
+                _ => (-1, 1)
+            };
+            assert_eq!(loc(piece, 4, 20, rotation).to_tetrio(), (4 + expected.0, 20 + expected.1), "{piece:?} {rotation:?}");
+        }
+    }
+}
+
+#[test]
+fn tetrio_corner_is_srs_box() {
+    for piece in PIECES {
+        for rotation in ROT {
+            let l = PieceLocation::from_tetrio(piece, rotation, 3, 10);
+            assert_eq!(sorted(l.blocks().to_vec()), srs_cells(piece, rotation, 3, 10), "{piece:?} {rotation:?}");
+        }
+    }
+}
+
+// without kicks, srs rotates every piece in place around its pivot
+#[test]
+fn srs_pivot_is_fixed() {
+    let board = Board { cols: [0; 10] };
+    let config = GameConfig { rotation: RotationSystem::SRS, ..Default::default() };
+    for piece in PIECES.into_iter().filter(|&p| p != Piece::O) {
+        for rotation in ROT {
+            let l = PieceLocation::from_srs(piece, rotation, 4, 10);
+            for to in [rotation.rotate_cw(), rotation.rotate_ccw()] {
+                let rotated = try_rotate(&board, &l, to, &config).unwrap();
+                assert_eq!(rotated.to_srs(), (4, 10), "{piece:?} {rotation:?} -> {to:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn o_srs_ignores_rotation() {
+    let cells = sorted(PieceLocation::from_srs(Piece::O, Rotation::North, 4, 10).blocks().to_vec());
+    for rotation in ROT {
+        assert_eq!(sorted(PieceLocation::from_srs(Piece::O, rotation, 4, 10).blocks().to_vec()), cells);
+    }
+}
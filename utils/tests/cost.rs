@@ -0,0 +1,86 @@
+use bitboard_traits::BitboardTrait;
+use rand::{SeedableRng, rngs::StdRng};
+use utils::{
+    data::{Board, Piece, PieceLocation, Rotation, Spin},
+    game::{GameConfig, Gravity},
+    movegen::{InputCost, movegen_piece, placement_costs},
+    perft::compare_locations
+};
+
+mod common;
+use common::{PIECES, stack};
+
+fn costs(board: &Board, piece: Piece, config: &GameConfig, cost: &InputCost) -> Vec<(PieceLocation, u32)> {
+    let mut arena = vec![];
+    placement_costs(&mut arena, board, piece, true, config, cost);
+    arena
+}
+
+fn cost_of(found: &[(PieceLocation, u32)], x: i8, y: i8, rotation: Rotation, spin: Spin) -> u32 {
+    found.iter()
+        .find(|(l, _)| (l.x, l.y, l.rotation, l.spin) == (x, y, rotation, spin))
+        .unwrap_or_else(|| panic!("({x}, {y}, {rotation:?}, {spin:?}) not found"))
+        .1
+}
+
+// same placements as the bitboard movegen, under every gravity
+#[test]
+fn same_placements() {
+    let mut rng = StdRng::seed_from_u64(8);
+    for _ in 0..20 {
+        let board = stack(&mut rng);
+        for gravity in [Gravity::Free, Gravity::Instant, Gravity::HarddropOnly] {
+            let config = GameConfig { gravity, ..Default::default() };
+            for piece in PIECES {
+                let mut expected = vec![];
+                movegen_piece(&mut expected, &board, piece, true, &config);
+                let found: Vec<_> = costs(&board, piece, &config, &InputCost::KEYS).into_iter().map(|(l, _)| l).collect();
+                if let Err(m) = compare_locations(&board, piece, &found, &expected) {
+                    panic!("{piece:?} ({gravity:?}) on\n{}\nonly with costs: {:?}\nonly in movegen: {:?}", board.to_ascii(), m.extra, m.missing);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn empty_board_keys() {
+    let found = costs(&Board::new(), Piece::T, &GameConfig::default(), &InputCost::KEYS);
+    // harddrop
+    assert_eq!(cost_of(&found, 4, 0, Rotation::North, Spin::None), 1);
+    // tap, harddrop
+    assert_eq!(cost_of(&found, 3, 0, Rotation::North, Spin::None), 2);
+    // das, harddrop
+    assert_eq!(cost_of(&found, 1, 0, Rotation::North, Spin::None), 2);
+    // das, tap, harddrop
+    assert_eq!(cost_of(&found, 2, 0, Rotation::North, Spin::None), 3);
+    // rotate, das, harddrop
+    assert_eq!(cost_of(&found, 0, 1, Rotation::East, Spin::None), 3);
+    // 180, harddrop
+    assert_eq!(cost_of(&found, 4, 1, Rotation::South, Spin::None), 2);
+}
+
+// with a long das, tapping 3 times is faster than holding
+#[test]
+fn empty_board_frames() {
+    let found = costs(&Board::new(), Piece::T, &GameConfig::default(), &InputCost::frames(10));
+    assert_eq!(cost_of(&found, 1, 0, Rotation::North, Spin::None), 4);
+    assert_eq!(cost_of(&found, 8, 0, Rotation::North, Spin::None), 5);
+    let found = costs(&Board::new(), Piece::T, &GameConfig::default(), &InputCost::frames(2));
+    assert_eq!(cost_of(&found, 1, 0, Rotation::North, Spin::None), 3);
+}
+
+// the tsd is the slot's upright placement plus a softdrop and a second rotation
+#[test]
+fn tsd() {
+    let board = Board::from_ascii("
+        ###.......
+        ##...#####
+        ###.######
+    ").unwrap();
+    let found = costs(&board, Piece::T, &GameConfig::default(), &InputCost::KEYS);
+    // cw, tap left, harddrop
+    assert_eq!(cost_of(&found, 3, 1, Rotation::East, Spin::None), 3);
+    // cw, tap left, softdrop, cw, harddrop
+    assert_eq!(cost_of(&found, 3, 1, Rotation::South, Spin::Full), 5);
+}
@@ -0,0 +1,221 @@
+use utils::{
+    data::{Board, Piece, PieceLocation, Rotation},
+    fumen::{Fumen, FumenError, FumenPage}
+};
+
+mod common;
+use common::loc;
+
+// fumen strings are built here straight from the v115 layout, not with the encoder: numbers are little endian base 64
+// digits, a field is runs of (diff * 240 + count - 1) in 2 digits, and a page's piece is
+// ((flags * 240 + pos) * 4 + rotation) * 8 + kind in 3 digits, with pos counted from the top left of the 23 row field
+
+const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+// rise, mirror, color (set on the first page), comment. a set lock bit means the piece doesn't lock
+const RISE: u64 = 1;
+const MIRROR: u64 = 2;
+const COLOR: u64 = 4;
+const COMMENT: u64 = 8;
+
+fn digits(mut v: u64, n: usize) -> String {
+    (0..n).map(|_| {
+        let c = TABLE[(v % 64) as usize] as char;
+        v /= 64;
+        c
+    }).collect()
+}
+
+// a field that doesn't change, and how many of the following pages don't change it either
+fn unchanged(repeat: u64) -> String {
+    digits(8 * 240 + 239, 2) + &digits(repeat, 1)
+}
+
+// kind: 1 I, 2 L, 3 O, 4 Z, 5 T, 6 J, 7 S. rotation: 0 south, 1 east, 2 north, 3 west. y is from the bottom
+fn action(kind: u64, rotation: u64, x: u64, y: u64, flags: u64) -> String {
+    digits(((flags * 240 + (22 - y) * 10 + x) * 4 + rotation) * 8 + kind, 3)
+}
+
+fn no_piece(flags: u64) -> String {
+    action(0, 0, 0, 22, flags)
+}
+
+fn cells(loc: &PieceLocation) -> Vec<(i8, i8)> {
+    let mut cells = loc.blocks().to_vec();
+    cells.sort();
+    cells
+}
+
+fn filled(board: &Board) -> Vec<(i8, i8)> {
+    let mut cells = vec![];
+    for x in 0..10 {
+        for y in 0..23 {
+            if board.cols[x] & (1 << y) > 0 {
+                cells.push((x as i8, y as i8));
+            }
+        }
+    }
+    cells.sort();
+    cells
+}
+
+fn same_pages(a: &[FumenPage], b: &[FumenPage]) {
+    assert_eq!(a.len(), b.len());
+    for (a, b) in a.iter().zip(b) {
+        assert_eq!(a.board.cols, b.board.cols);
+        assert_eq!(a.piece.as_ref().map(cells), b.piece.as_ref().map(cells));
+        assert_eq!(a.comment, b.comment);
+        assert_eq!(a.lock, b.lock);
+    }
+}
+
+#[test]
+fn empty() {
+    assert_eq!(unchanged(0) + &no_piece(COLOR), "vhAAgH");
+    let fumen = Fumen::decode("v115@vhAAgH").unwrap();
+    assert_eq!(fumen.pages.len(), 1);
+    let page = &fumen.pages[0];
+    assert_eq!(page.board.cols, [0; 10]);
+    assert!(page.piece.is_none() && page.comment.is_empty() && page.lock);
+    assert_eq!(fumen.encode(), "v115@vhAAgH");
+}
+
+// pages that don't touch the field share one field with a repeat count, however many there are
+#[test]
+fn repeated_pages() {
+    let s = format!("v115@{}{}{}{}", unchanged(2), no_piece(COLOR), no_piece(0), no_piece(0));
+    let fumen = Fumen::decode(&s).unwrap();
+    assert_eq!(fumen.pages.len(), 3);
+    assert!(fumen.pages.iter().all(|p| p.board.cols == [0; 10] && p.piece.is_none()));
+    assert_eq!(fumen.encode(), s);
+
+    // once the repeat runs out the next page has its own field again
+    let s = format!("v115@{}{}{}{}{}", unchanged(1), no_piece(COLOR), no_piece(0), unchanged(0), no_piece(0));
+    assert_eq!(Fumen::decode(&s).unwrap().pages.len(), 3);
+    assert_eq!(Fumen::decode(&s).unwrap().encode(), format!("v115@{}{}{}{}", unchanged(2), no_piece(COLOR), no_piece(0), no_piece(0)));
+}
+
+// a locked piece shows up in the next page's field, a piece that doesn't lock doesn't
+#[test]
+fn pieces() {
+    let i = action(1, 2, 4, 0, COLOR);
+    let t = action(5, 2, 1, 1, 16);
+    let s = format!("v115@{}{i}{}{t}{}{}", unchanged(0), unchanged(0), unchanged(0), no_piece(0));
+    let fumen = Fumen::decode(&s).unwrap();
+    assert_eq!(fumen.pages.len(), 3);
+    assert_eq!(cells(fumen.pages[0].piece.as_ref().unwrap()), [(3, 0), (4, 0), (5, 0), (6, 0)]);
+    assert_eq!(filled(&fumen.pages[1].board), [(3, 0), (4, 0), (5, 0), (6, 0)]);
+    assert!(!fumen.pages[1].lock);
+    assert_eq!(cells(fumen.pages[1].piece.as_ref().unwrap()), [(0, 1), (1, 1), (1, 2), (2, 1)]);
+    assert_eq!(filled(&fumen.pages[2].board), filled(&fumen.pages[1].board));
+}
+
+// fumen puts O, I, S and Z somewhere other than our position in some rotations. these are the cells each lands on
+#[test]
+fn offsets() {
+    let cases = [
+        (3, 2, 4, 1, [(4, 0), (4, 1), (5, 0), (5, 1)]),
+        (3, 0, 5, 1, [(5, 0), (5, 1), (6, 0), (6, 1)]),
+        (3, 3, 5, 2, [(5, 1), (5, 2), (6, 1), (6, 2)]),
+        (1, 0, 5, 0, [(4, 0), (5, 0), (6, 0), (7, 0)]),
+        (1, 3, 0, 2, [(0, 0), (0, 1), (0, 2), (0, 3)]),
+        (7, 2, 4, 1, [(3, 0), (4, 0), (4, 1), (5, 1)]),
+        (4, 2, 4, 1, [(3, 1), (4, 0), (4, 1), (5, 0)]),
+        (4, 3, 5, 1, [(5, 0), (5, 1), (6, 1), (6, 2)])
+    ];
+    for (kind, rotation, x, y, expected) in cases {
+        let s = format!("v115@{}{}", unchanged(0), action(kind, rotation, x, y, COLOR));
+        let fumen = Fumen::decode(&s).unwrap();
+        let loc = fumen.pages[0].piece.clone().unwrap();
+        assert_eq!(cells(&loc), expected, "{loc:?}");
+        assert_eq!(fumen.encode(), s, "{loc:?}");
+    }
+}
+
+#[test]
+fn comments() {
+    // "hello": length 5, then 4 characters per 5 digits, each its index in the printable ascii table
+    let index = |c: char| c as u64 - 32;
+    let chunk = |s: &str| s.chars().rev().fold(0, |acc, c| acc * 96 + index(c));
+    let s = format!("v115@{}{}{}{}{}{}", unchanged(1), no_piece(COLOR | COMMENT), digits(5, 2), digits(chunk("hell"), 5), digits(chunk("o"), 5), no_piece(0));
+    let fumen = Fumen::decode(&s).unwrap();
+    assert_eq!(fumen.pages.len(), 2);
+    // a comment carries over to pages that don't set one
+    assert_eq!(fumen.pages[0].comment, "hello");
+    assert_eq!(fumen.pages[1].comment, "hello");
+    assert_eq!(fumen.encode(), s);
+
+    // quiz comments are escaped like javascript's escape(), and give the hold and queue
+    let fumen = Fumen { pages: vec![FumenPage { board: Board { cols: [0; 10] }, piece: None, comment: "#Q=[S](T)ZLJ".into(), lock: true }] };
+    let encoded = fumen.encode();
+    let decoded = Fumen::decode(&encoded).unwrap();
+    assert_eq!(decoded.pages[0].comment, "#Q=[S](T)ZLJ");
+    assert_eq!(decoded.pages[0].queue(), Some((Some(Piece::S), vec![Piece::T, Piece::Z, Piece::L, Piece::J])));
+    assert_eq!(decoded.game().hold, Some(Piece::S));
+}
+
+// rise pushes the field up with the garbage row under the floor coming in at the bottom, mirror flips it. both happen
+// after the page's piece locks
+#[test]
+fn rise_and_mirror() {
+    // the garbage row (the last 10 cells) filled gray except column 0
+    let field = digits(8 * 240 + 230, 2) + &digits(16 * 240 + 8, 2);
+    let s = format!("v115@{field}{}{}{}", no_piece(COLOR | RISE), unchanged(0), no_piece(0));
+    let fumen = Fumen::decode(&s).unwrap();
+    assert_eq!(fumen.pages[0].board.cols, [0; 10]);
+    assert_eq!(fumen.pages[1].board.cols, [0, 1, 1, 1, 1, 1, 1, 1, 1, 1]);
+
+    let s = format!("v115@{}{}{}{}", unchanged(0), action(2, 2, 1, 0, COLOR | MIRROR), unchanged(0), no_piece(0));
+    let fumen = Fumen::decode(&s).unwrap();
+    assert_eq!(cells(fumen.pages[0].piece.as_ref().unwrap()), [(0, 0), (1, 0), (2, 0), (2, 1)]);
+    assert_eq!(filled(&fumen.pages[1].board), [(7, 0), (7, 1), (8, 0), (9, 0)]);
+}
+
+#[test]
+fn round_trips() {
+    let board = Board::from_ascii("
+        #.........
+        ##...#####
+        ###.######
+    ").unwrap();
+    let placements = [
+        loc(Piece::T, 3, 5, Rotation::South),
+        loc(Piece::I, 1, 8, Rotation::East),
+        loc(Piece::O, 6, 4, Rotation::North),
+        loc(Piece::S, 8, 7, Rotation::West),
+        loc(Piece::Z, 5, 9, Rotation::South),
+        loc(Piece::L, 1, 12, Rotation::West),
+        loc(Piece::J, 7, 12, Rotation::North)
+    ];
+    let mut fumen = Fumen::from_placements(&board, &placements, Some(Piece::I), &[Piece::T, Piece::I, Piece::O]);
+    fumen.pages[2].comment = "a comment with spaces & symbols %".into();
+    fumen.pages[3].lock = false;
+    fumen.pages.push(FumenPage { board: fumen.pages[3].board, piece: None, comment: String::new(), lock: true });
+    fumen.pages.push(fumen.pages[4].clone());
+    // enough pages to need '?' separators
+    assert!(fumen.encode().contains('?'));
+
+    let decoded = Fumen::decode(&fumen.encode()).unwrap();
+    same_pages(&decoded.pages, &fumen.pages);
+    assert_eq!(decoded.placements().len(), fumen.placements().len());
+    assert_eq!(decoded.encode(), fumen.encode());
+
+    // every piece in every rotation, one per page
+    let mut pages = vec![];
+    for piece in [Piece::I, Piece::O, Piece::T, Piece::L, Piece::J, Piece::S, Piece::Z] {
+        for rotation in [Rotation::North, Rotation::East, Rotation::South, Rotation::West] {
+            pages.push(FumenPage { board: Board { cols: [0; 10] }, piece: Some(loc(piece, 4, 10, rotation)), comment: String::new(), lock: false });
+        }
+    }
+    let fumen = Fumen { pages };
+    same_pages(&Fumen::decode(&fumen.encode()).unwrap().pages, &fumen.pages);
+}
+
+#[test]
+fn errors() {
+    assert_eq!(Fumen::decode("v110@vhAAgH").unwrap_err(), FumenError::Version);
+    assert_eq!(Fumen::decode("v115@vh").unwrap_err(), FumenError::Truncated);
+    assert!(matches!(Fumen::decode("v115@vh!AgH").unwrap_err(), FumenError::Character('!')));
+    // a run past the end of the field
+    assert_eq!(Fumen::decode(&format!("v115@{}{}", digits(8 * 240 + 234, 2), digits(8 * 240 + 5, 2))).unwrap_err(), FumenError::Corrupt);
+}
@@ -0,0 +1,58 @@
+use utils::{
+    data::{Board, Piece, Rotation},
+    game::{Game, GameConfig, SpinRule}
+};
+
+mod common;
+use common::loc;
+
+// after is advance without touching the state or rolling a hole, so the same call gives the same state every time
+#[test]
+fn after_is_pure() {
+    let board = Board::from_ascii("
+        ..........
+        .########.
+    ").unwrap();
+    let o = loc(Piece::O, 4, 1, Rotation::North);
+    let game = Game { board, incoming_garbage: 3, ..Game::new_empty() };
+    let state = game.state();
+    let (after, info) = state.after(Piece::O, &o, 9);
+    assert_eq!(state.incoming_garbage, 3);
+    assert_eq!(state.board.cols[4], 0b1);
+    assert_eq!(info.lines_received, 3);
+    assert_eq!(after.incoming_garbage, 0);
+    // three garbage lines with the hole in column 9 under what was there
+    assert!((0..9).all(|x| after.board.cols[x] & 0b111 == 0b111));
+    assert_eq!(after.board.cols[9] & 0b111, 0);
+    assert_eq!(state.after(Piece::O, &o, 9).0.board.cols, after.board.cols);
+}
+
+// a state plays out the same as the game it came from, and keeps pointing at that game's config
+#[test]
+fn state_follows_game() {
+    let config = GameConfig { spins: SpinRule::AllSpin, ..Default::default() };
+    let mut game = Game { incoming_garbage: 2, config, ..Game::new_empty() };
+    let mut state = game.state();
+    assert!(std::ptr::eq(state.config, &game.config));
+    assert_eq!(state.config.spins, SpinRule::AllSpin);
+
+    let placements = [
+        (Piece::I, loc(Piece::I, 1, 0, Rotation::North)),
+        (Piece::T, loc(Piece::O, 8, 0, Rotation::North)),
+        (Piece::J, loc(Piece::J, 5, 0, Rotation::North)),
+        (Piece::L, loc(Piece::T, 1, 1, Rotation::North))
+    ];
+    let mut played = vec![];
+    for (next, loc) in &placements {
+        let (after, info) = state.after(*next, loc, 7);
+        state = after;
+        played.push((info.lines_cleared, info.lines_received, info.outgoing_attack));
+    }
+    let (board, hold, b2b, combo) = (state.board, state.hold, state.b2b, state.combo);
+
+    for (i, (next, loc)) in placements.iter().enumerate() {
+        let info = game.advance_with(*next, loc, || 7);
+        assert_eq!((info.lines_cleared, info.lines_received, info.outgoing_attack), played[i]);
+    }
+    assert_eq!((game.board.cols, game.hold, game.b2b, game.combo), (board.cols, hold, b2b, combo));
+}
@@ -0,0 +1,106 @@
+use bitboard_traits::BitboardTrait;
+use rand::{SeedableRng, rngs::StdRng};
+use utils::{
+    data::{Board, PieceLocation, Spin},
+    game::{GameConfig, Gravity},
+    movegen::sonic_drop,
+    perft::compare_locations,
+    reference::movegen_reference
+};
+
+mod common;
+use common::{PIECES, placements, stack, with_gravity};
+
+fn key(l: &PieceLocation) -> (i8, i8, u8, u8) {
+    (l.x, l.y, l.rotation as u8, l.spin as u8)
+}
+
+fn subset(a: &[PieceLocation], b: &[PieceLocation]) -> bool {
+    a.iter().all(|l| b.iter().any(|m| key(l) == key(m)))
+}
+
+const WALL: &str = "
+    ..#.......
+    ..#.......
+    ..#.......
+    ..#.......
+    ..#.......
+
+";
+
+// with free movement but a reset limit high enough to never matter, the bfs has to agree with the reference
+#[test]
+fn unlimited_resets_match_reference() {
+    let mut rng = StdRng::seed_from_u64(5);
+    for _ in 0..20 {
+        let board = stack(&mut rng);
+        for piece in PIECES {
+            let mut reference = vec![];
+            movegen_reference(&mut reference, &board, piece, true, &GameConfig::default());
+            let found = placements(&board, piece, &with_gravity(Gravity::Free, Some(u8::MAX)));
+            if let Err(m) = compare_locations(&board, piece, &found, &reference) {
+                panic!("{piece:?} on\n{}\nonly in limited: {:?}\nonly in reference: {:?}", board.to_ascii(), m.extra, m.missing);
+            }
+        }
+    }
+}
+
+// fewer resets can only lose placements
+#[test]
+fn resets_are_monotonic() {
+    let mut rng = StdRng::seed_from_u64(6);
+    for _ in 0..10 {
+        let board = stack(&mut rng);
+        for piece in PIECES {
+            let mut prev = placements(&board, piece, &with_gravity(Gravity::Free, Some(0)));
+            for n in 1..4 {
+                let next = placements(&board, piece, &with_gravity(Gravity::Free, Some(n)));
+                assert!(subset(&prev, &next), "{piece:?} with {n} resets on\n{}", board.to_ascii());
+                prev = next;
+            }
+            assert!(subset(&prev, &placements(&board, piece, &GameConfig::default())));
+        }
+    }
+}
+
+// without softdrop every placement is a straight drop from spawn height, so nothing is tucked under an overhang
+#[test]
+fn harddrop_only() {
+    let mut rng = StdRng::seed_from_u64(7);
+    for _ in 0..20 {
+        let board = stack(&mut rng);
+        for piece in PIECES {
+            let found = placements(&board, piece, &with_gravity(Gravity::HarddropOnly, None));
+            assert!(!found.is_empty());
+            for l in &found {
+                assert_eq!(l.spin, Spin::None, "{l:?}");
+                assert_eq!(sonic_drop(&board, &PieceLocation { y: 30, ..l.clone() }).y, l.y, "{l:?} on\n{}", board.to_ascii());
+            }
+            assert!(subset(&found, &placements(&board, piece, &GameConfig::default())));
+        }
+    }
+}
+
+// at 20g a piece spawned right of a wall falls next to it and can't climb over
+#[test]
+fn instant_gravity_wall() {
+    let board = Board::from_ascii(WALL).unwrap();
+    for piece in PIECES {
+        let free = placements(&board, piece, &GameConfig::default());
+        let instant = placements(&board, piece, &with_gravity(Gravity::Instant, None));
+        assert!(free.iter().any(|l| l.x < 2), "{piece:?}");
+        assert!(instant.iter().all(|l| l.blocks().iter().all(|&(x, _)| x > 2)), "{piece:?}: {instant:?}");
+        assert!(subset(&instant, &free));
+    }
+}
+
+// 20g with no resets left: the piece locks wherever it first lands
+#[test]
+fn instant_gravity_no_resets() {
+    let board = Board::new();
+    for piece in PIECES {
+        let found = placements(&board, piece, &with_gravity(Gravity::Instant, Some(0)));
+        assert_eq!(found.len(), 1, "{piece:?}: {found:?}");
+        assert_eq!(found[0].y, sonic_drop(&board, &found[0]).y);
+    }
+}
@@ -0,0 +1,208 @@
+use bitboard_traits::BitboardTrait;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use utils::{
+    data::{Board, Piece, PieceLocation, Rotation, Spin},
+    game::{GameConfig, Gravity},
+    input::{Finesse, Handling, KeygenError, MovementAction::{self, *}, keygen, replay_keys, timeline, verify},
+    movegen::movegen_piece,
+    queue::SeededBag,
+    replay::{Key, PlayerReplay, ReplayEvent, ReplayOptions},
+    rotation::RotationSystem
+};
+
+mod common;
+use common::{PIECES, stack};
+
+fn t(x: i8, y: i8, rotation: Rotation, spin: Spin) -> PieceLocation {
+    PieceLocation { piece: Piece::T, x, y, rotation, spin }
+}
+
+fn tsd_board() -> Board {
+    Board::from_ascii("
+        ###.......
+        ##...#####
+        ###.######
+    ").unwrap()
+}
+
+// every placement movegen finds gets keys, and replaying them lands exactly there
+#[test]
+fn keys_for_every_placement() {
+    let mut rng = StdRng::seed_from_u64(9);
+    let finesses = [Finesse::Keys, Finesse::Taps, Finesse::Frames(Handling::default())];
+    for _ in 0..5 {
+        let board = stack(&mut rng);
+        for gravity in [Gravity::Free, Gravity::Instant] {
+            let config = GameConfig { gravity, ..Default::default() };
+            for piece in PIECES {
+                let mut found = vec![];
+                movegen_piece(&mut found, &board, piece, true, &config);
+                for loc in &found {
+                    for finesse in &finesses {
+                        let keys = keygen(&board, loc, finesse, &config)
+                            .unwrap_or_else(|e| panic!("{e}: no keys for {loc:?} ({finesse:?}, {gravity:?}) on\n{}", board.to_ascii()));
+                        assert!(verify(&board, &keys, loc, &config), "{keys:?} don't land at {loc:?} on\n{}", board.to_ascii());
+                        if *finesse == Finesse::Taps {
+                            assert!(!keys.contains(&DASLeft) && !keys.contains(&DASRight));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn empty_board() {
+    let board = Board::new();
+    let config = GameConfig::default();
+    let keys = |loc: &PieceLocation, finesse: Finesse| keygen(&board, loc, &finesse, &config).unwrap();
+
+    assert_eq!(keys(&t(4, 0, Rotation::North, Spin::None), Finesse::Keys), [Harddrop]);
+    assert_eq!(keys(&t(1, 0, Rotation::North, Spin::None), Finesse::Keys), [DASLeft, Harddrop]);
+    assert_eq!(keys(&t(1, 0, Rotation::North, Spin::None), Finesse::Taps), [TapLeft, TapLeft, TapLeft, Harddrop]);
+    assert_eq!(keys(&t(4, 1, Rotation::South, Spin::None), Finesse::Keys), [Rotate180, Harddrop]);
+
+    // 3 frames of taps beat 10 frames of das, but das wins once it is short enough
+    let slow = Handling { das: 10.0, arr: 0.0, ..Default::default() };
+    let fast = Handling { das: 2.0, ..slow };
+    assert_eq!(keys(&t(1, 0, Rotation::North, Spin::None), Finesse::Frames(slow)), [TapLeft, TapLeft, TapLeft, Harddrop]);
+    assert_eq!(keys(&t(1, 0, Rotation::North, Spin::None), Finesse::Frames(fast)), [DASLeft, Harddrop]);
+}
+
+#[test]
+fn tsd() {
+    let board = tsd_board();
+    let config = GameConfig::default();
+    let target = t(3, 1, Rotation::South, Spin::Full);
+    let keys = keygen(&board, &target, &Finesse::Keys, &config).unwrap();
+    assert_eq!(keys.len(), 5, "{keys:?}");
+    assert!(keys.contains(&Softdrop));
+    assert!(verify(&board, &keys, &target, &config));
+
+    // same cells, but the spin has to match too
+    assert!(!verify(&board, &keys, &t(3, 1, Rotation::South, Spin::None), &config));
+    // and harddropping straight from spawn doesn't get there at all
+    assert!(matches!(keygen(&board, &t(3, 1, Rotation::South, Spin::None), &Finesse::Keys, &config), Err(KeygenError::Unreachable(_))));
+}
+
+#[test]
+fn keygen_errors() {
+    let mut board = Board::new();
+    board.cols[4] = u64::MAX;
+    let target = t(0, 0, Rotation::North, Spin::None);
+    assert!(matches!(keygen(&board, &target, &Finesse::Keys, &GameConfig::default()), Err(KeygenError::Blocked(Piece::T))));
+    // overlapping the stack is just unreachable
+    let board = tsd_board();
+    assert!(matches!(keygen(&board, &t(1, 0, Rotation::North, Spin::None), &Finesse::Keys, &GameConfig::default()), Err(KeygenError::Unreachable(_))));
+    assert_eq!(Piece::from_char('t'), Ok(Piece::T));
+    assert!(Piece::from_char('x').is_err());
+}
+
+#[test]
+fn replay_rejects() {
+    let board = tsd_board();
+    let config = GameConfig::default();
+    let replay = |keys: &[MovementAction]| replay_keys(&board, Piece::T, keys, &config);
+
+    assert!(replay(&[TapLeft]).is_none());
+    assert!(replay(&[Harddrop, TapLeft]).is_none());
+    let target = t(3, 1, Rotation::East, Spin::None);
+    assert!(verify(&board, &[Hold, RotateCW, TapLeft, Harddrop], &target, &config));
+    assert!(!verify(&board, &[RotateCW, Harddrop], &target, &config));
+
+    let harddrop_only = GameConfig { gravity: Gravity::HarddropOnly, ..Default::default() };
+    assert!(replay_keys(&board, Piece::T, &[Softdrop, Harddrop], &harddrop_only).is_none());
+}
+
+// keys turned into a timeline and played by the replay simulator (frame by frame das, arr and softdrop) end up where
+// keygen meant them to, piece after piece
+#[test]
+fn timeline_simulates() {
+    let handling = Handling { das: 8.0, arr: 2.0, sdf: 1.0, dcd: 1.0 };
+    let options = ReplayOptions {
+        seed: 1234,
+        das: 8.0,
+        arr: 2.0,
+        // 20x the 0.05 minimum softdrop gravity, 1 row a frame
+        sdf: 20.0,
+        gravity: 0.02,
+        lock_delay: 30,
+        next_count: 5,
+        rotation: RotationSystem::default(),
+        spins: Default::default()
+    };
+    let config = GameConfig::default();
+    let mut pieces = vec![];
+    SeededBag::new(options.seed).extend(&mut pieces, 2);
+
+    let mut rng = StdRng::seed_from_u64(10);
+    let mut board = Board::new();
+    let mut targets = vec![];
+    let mut events = vec![];
+    let mut start = 0;
+    for &piece in &pieces[..12] {
+        let mut found = vec![];
+        movegen_piece(&mut found, &board, piece, true, &config);
+        let target = found[rng.random_range(0..found.len())].clone();
+        let keys = keygen(&board, &target, &Finesse::Frames(handling), &config).unwrap();
+        for mut e in timeline(&board, piece, &keys, &config, &handling, None).unwrap() {
+            e.frame += start;
+            events.push(e);
+        }
+        start = events.last().unwrap().frame + 1;
+        board.put_piece(&target);
+        board.remove_lines();
+        targets.push(target);
+    }
+
+    let replay = PlayerReplay { username: None, round: 0, options, events };
+    let positions = replay.simulate();
+    assert_eq!(positions.len(), targets.len());
+    for (p, target) in positions.iter().zip(&targets) {
+        let cells = |l: &PieceLocation| {
+            let mut b = l.blocks();
+            b.sort();
+            b
+        };
+        assert_eq!(cells(&p.location), cells(target), "{:?} instead of {target:?}", p.location);
+    }
+}
+
+#[test]
+fn timeline_pps() {
+    let board = Board::new();
+    let config = GameConfig::default();
+    let handling = Handling { das: 8.0, arr: 0.0, sdf: f32::INFINITY, dcd: 1.0 };
+    let keys = [RotateCW, DASLeft, Softdrop, RotateCW, Harddrop];
+    let events = timeline(&board, Piece::T, &keys, &config, &handling, None).unwrap();
+
+    // every key goes down then up, in order
+    assert_eq!(events.len(), 2 * keys.len());
+    for (pair, action) in events.chunks(2).zip(keys) {
+        assert_eq!(pair[0].event, ReplayEvent::KeyDown(action.key().unwrap()));
+        assert_eq!(pair[1].event, ReplayEvent::KeyUp(action.key().unwrap()));
+    }
+    let time = |i: usize| events[i].frame as f64 + events[i].subframe;
+    assert!((1..events.len()).all(|i| time(i) >= time(i - 1)));
+    // das waits out dcd after the rotation
+    assert!(time(3) - time(2) >= 8.0 + 1.0);
+
+    // at 1 pps the harddrop happens a second in, at 100 pps as soon as it can
+    let paced = timeline(&board, Piece::T, &keys, &config, &handling, Some(1.0)).unwrap();
+    assert_eq!(paced.last().unwrap().frame, 60);
+    assert_eq!(paced.last().unwrap().event, ReplayEvent::KeyUp(Key::HardDrop));
+    let fast = timeline(&board, Piece::T, &keys, &config, &handling, Some(100.0)).unwrap();
+    assert_eq!(fast[0].frame, 0);
+
+    assert!(timeline(&board, Piece::T, &[TapLeft], &config, &handling, None).is_none());
+}
+
+// tetr.io's handling menu, read the way replays are simulated
+#[test]
+fn tetrio_handling() {
+    assert_eq!(Handling::from_tetrio(8.0, 0.0, 41.0, 1.0, 0.0), Handling { das: 8.0, arr: 0.0, sdf: f32::INFINITY, dcd: 1.0 });
+    // at no gravity softdrop still moves 0.05 rows a frame per sdf
+    assert_eq!(Handling::from_tetrio(10.0, 2.0, 20.0, 1.0, 0.0).sdf, 1.0);
+    assert_eq!(Handling::from_tetrio(10.0, 2.0, 20.0, 1.0, 0.5).sdf, 10.0);
+}
@@ -0,0 +1,127 @@
+use utils::{
+    game::Game,
+    perft::{perft, perft_compare}
+};
+
+// node counts for a few positions, checked once against the brute force reference (utils::reference) when they were pinned.
+// a change here means movegen found or lost placements, so run perft_compare on the position before updating them
+
+const EMPTY: &str = "
+    queue: IOLJSZT
+";
+
+// overhangs and tucks, lots of t spin and kick setups
+const OVERHANGS: &str = "
+    queue: TIOLJSZT
+    #.........
+    ##........
+    #.........
+    ##........
+    #.........
+    #...######
+    ##.#######
+    #..#######
+    ##.#######
+";
+
+const CHEESE: &str = "
+    queue: TIOLJSZT
+    ##...#####
+    #....#####
+    #.########
+    #...######
+    ##.#######
+    #######.##
+    ###.######
+    ######.###
+    .#########
+";
+
+// max height 20, so movegen searches from spawn instead of the surface
+const TALL: &str = "
+    queue: TIOLJSZT
+    #.........
+    #.........
+    #.........
+    #.........
+    #.........
+    #.........
+    #.........
+    #.........
+    #.........
+    #...######
+    #...######
+    #...######
+    #...######
+    #...######
+    #...######
+    #...######
+    #...######
+    ##.#######
+    ##.#######
+    ##.#######
+";
+
+// stacked past spawn, so the piece has to be forced in above row 21
+const OVER_SPAWN: &str = "
+    queue: TIOLJSZT
+    ##.#######
+    #######.##
+    ##.#######
+    #######.##
+    ##.#######
+    #######.##
+    ##.#######
+    #######.##
+    ##.#######
+    #######.##
+    ##.#######
+    #######.##
+    ##.#######
+    #######.##
+    ##.#######
+    #######.##
+    ##.#######
+    #######.##
+    ##.#######
+    #######.##
+    ##.#######
+    #######.##
+";
+
+fn check(position: &str, no_hold: &[usize], hold: &[usize]) {
+    let (game, queue) = Game::from_ascii(position).unwrap();
+    for (counts, with_hold) in [(no_hold, false), (hold, true)] {
+        for (d, &nodes) in counts.iter().enumerate() {
+            assert_eq!(perft(&game, &queue, d + 1, with_hold), nodes, "depth {} hold {with_hold}", d + 1);
+        }
+        if let Err(m) = perft_compare(&game, &queue, 2, with_hold) {
+            panic!("{:?} on\n{}\nonly in movegen: {:?}\nonly in reference: {:?}", m.piece, m.board.to_ascii(), m.extra, m.missing);
+        }
+    }
+}
+
+#[test]
+fn empty() {
+    check(EMPTY, &[17, 153, 5266, 188334], &[26, 1190, 62996]);
+}
+
+#[test]
+fn overhangs() {
+    check(OVERHANGS, &[37, 716, 7223], &[56, 2016, 110098]);
+}
+
+#[test]
+fn cheese() {
+    check(CHEESE, &[35, 620, 5798], &[52, 1693, 85134]);
+}
+
+#[test]
+fn tall() {
+    check(TALL, &[34, 573, 5397], &[50, 1596, 79835]);
+}
+
+#[test]
+fn over_spawn() {
+    check(OVER_SPAWN, &[34, 586, 3723], &[51, 1455, 62086]);
+}
@@ -0,0 +1,112 @@
+use bitboard_traits::BitboardTrait;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use utils::{
+    data::Board,
+    game::{GameConfig, SpinRule},
+    movegen::movegen_piece,
+    perft::compare_locations,
+    reference::movegen_reference,
+    rotation::RotationSystem
+};
+
+mod common;
+use common::{PIECES, noise};
+
+// differential tests: the bitboard movegen has to find exactly the same locations and spins as the brute force reference
+// on random boards. the seeds are fixed so a failure always reproduces
+
+const SYSTEMS: [RotationSystem; 5] = [
+    RotationSystem::SRS_PLUS,
+    RotationSystem::SRS,
+    RotationSystem::SRS_X,
+    RotationSystem::ARS,
+    RotationSystem::NO_KICK
+];
+
+const RULES: [SpinRule; 4] = [SpinRule::TSpins, SpinRule::AllMini, SpinRule::AllSpin, SpinRule::MiniPlus];
+
+fn check(board: &Board, force: bool) {
+    let mut found = vec![];
+    let mut reference = vec![];
+    for rotation in SYSTEMS {
+        for spins in RULES {
+            let config = GameConfig { spins, rotation, ..Default::default() };
+            for piece in PIECES {
+                found.clear();
+                reference.clear();
+                movegen_piece(&mut found, board, piece, force, &config);
+                movegen_reference(&mut reference, board, piece, force, &config);
+                if let Err(m) = compare_locations(board, piece, &found, &reference) {
+                    panic!("{piece:?} ({config:?}, force {force}) on\n{}\nonly in movegen: {:?}\nonly in reference: {:?}", board.to_ascii(), m.extra, m.missing);
+                }
+            }
+        }
+    }
+}
+
+// solid columns of random height with a few cells carved out under the top, so pieces have to tuck and kick into them
+fn carved(rng: &mut StdRng, height: usize) -> Board {
+    let mut board = Board::new();
+    for x in 0..10 {
+        let h = rng.random_range(0..=height);
+        board.cols[x] = (1 << h) - 1;
+        for _ in 0..rng.random_range(0..3) {
+            if h > 1 {
+                let y = rng.random_range(h.saturating_sub(4)..h - 1);
+                board.cols[x] &= !(1 << y);
+            }
+        }
+    }
+    board
+}
+
+// garbage lines with one hole each
+fn cheese(rng: &mut StdRng, lines: usize) -> Board {
+    let mut board = Board::new();
+    for y in 0..lines {
+        let hole = rng.random_range(0..10);
+        for x in (0..10).filter(|&x| x != hole) {
+            board.cols[x] |= 1 << y;
+        }
+    }
+    board
+}
+
+#[test]
+fn noise_boards() {
+    let mut rng = StdRng::seed_from_u64(1);
+    for _ in 0..60 {
+        let height = rng.random_range(2..14);
+        check(&noise(&mut rng, height, 0.8), true);
+    }
+}
+
+#[test]
+fn carved_boards() {
+    let mut rng = StdRng::seed_from_u64(2);
+    for _ in 0..60 {
+        let height = rng.random_range(2..16);
+        check(&carved(&mut rng, height), true);
+    }
+}
+
+#[test]
+fn cheese_boards() {
+    let mut rng = StdRng::seed_from_u64(3);
+    for _ in 0..40 {
+        let lines = rng.random_range(1..12);
+        check(&cheese(&mut rng, lines), true);
+    }
+}
+
+// boards high enough that movegen starts from spawn, with and without forcing the spawn upwards
+#[test]
+fn tall_boards() {
+    let mut rng = StdRng::seed_from_u64(4);
+    for _ in 0..40 {
+        let height = rng.random_range(18..25);
+        let board = if rng.random_bool(0.5) { carved(&mut rng, height) } else { noise(&mut rng, height, 0.8) };
+        check(&board, true);
+        check(&board, false);
+    }
+}
@@ -0,0 +1,71 @@
+use utils::{
+    data::{Piece::*, Rotation},
+    game::SpinRule,
+    replay::{ReplayPosition, parse_replay}
+};
+
+fn positions(file: &str) -> Vec<(Option<String>, Vec<ReplayPosition>)> {
+    let json = std::fs::read_to_string(format!("tests/fixtures/{file}")).unwrap();
+    parse_replay(&json).unwrap().iter().map(|p| (p.username.clone(), p.simulate())).collect()
+}
+
+fn placed(p: &ReplayPosition) -> (utils::data::Piece, i8, i8, Rotation) {
+    (p.location.piece, p.location.x, p.location.y, p.location.rotation)
+}
+
+// old layout: options in the first "full" event. a harddrop at spawn, a tap left and a rotate, then hold and das right
+#[test]
+fn sprint_ttr() {
+    let players = positions("sprint.ttr");
+    assert_eq!(players.len(), 1);
+    let (username, positions) = &players[0];
+    assert_eq!(username.as_deref(), Some("sprinter"));
+    assert_eq!(positions.len(), 3);
+
+    assert_eq!(positions[0].queue, [I, T, J, S, O, L]);
+    assert_eq!(positions[1].queue, [T, J, S, O, L, Z]);
+    assert_eq!(positions[2].queue, [J, S, O, L, Z, O]);
+    assert_eq!(positions.iter().map(|p| p.frame).collect::<Vec<_>>(), [10, 23, 46]);
+
+    assert_eq!(placed(&positions[0]), (I, 4, 0, Rotation::North));
+    assert_eq!(placed(&positions[1]), (T, 3, 2, Rotation::East));
+    // the j went to hold, so the s came out and was dased to the wall
+    assert_eq!(placed(&positions[2]), (S, 8, 0, Rotation::North));
+    assert_eq!(positions[2].game.board.cols, [0, 0, 0, 15, 5, 1, 1, 0, 0, 0]);
+    assert_eq!(positions[2].game.hold, None);
+}
+
+// new layout: one round of two players. only confirmed garbage counts, and it comes up with the hole it was sent with
+#[test]
+fn versus_ttrm() {
+    let players = positions("versus.ttrm");
+    assert_eq!(players.len(), 2);
+    let (alice, positions) = &players[0];
+    assert_eq!(alice.as_deref(), Some("alice"));
+    assert_eq!(positions[0].queue, [S, I, O, T, L, J]);
+    assert_eq!(
+        positions.iter().map(placed).collect::<Vec<_>>(),
+        [(S, 4, 0, Rotation::North), (I, 3, 2, Rotation::North), (O, 4, 5, Rotation::North)]
+    );
+    assert_eq!(positions[1].game.incoming_garbage, 0);
+    assert_eq!(positions[1].info.lines_received, 2);
+    let board = &positions[2].game.board;
+    assert_eq!(board.cols[3] & 0b11, 0);
+    assert!((0..10).filter(|&x| x != 3).all(|x| board.cols[x] & 0b11 == 0b11));
+
+    let (bob, positions) = &players[1];
+    assert_eq!(bob.as_deref(), Some("bob"));
+    assert_eq!(positions[0].queue, [S, L, I, O, J, T]);
+    assert_eq!(placed(&positions[0]), (S, 4, 1, Rotation::South));
+}
+
+#[test]
+fn options() {
+    let json = std::fs::read_to_string("tests/fixtures/versus.ttrm").unwrap();
+    let players = parse_replay(&json).unwrap();
+    let options = &players[0].options;
+    assert_eq!((options.seed, options.das, options.arr, options.sdf, options.next_count), (7, 8.0, 0.0, 41.0, 5));
+    assert_eq!(options.spins, SpinRule::AllMini);
+    assert!(parse_replay("{}").is_err());
+    assert!(parse_replay("not json").is_err());
+}
@@ -0,0 +1,87 @@
+use bitboard_traits::BitboardTrait;
+use utils::{
+    data::{Board, Piece, Rotation, ROT},
+    movegen::try_rotate,
+    rotation::RotationSystem
+};
+
+mod common;
+use common::{loc, with_rotation};
+
+// srs+ I kicks as they were hardcoded before rotation systems existed, already shifted onto the I's center block
+#[test]
+fn srs_plus_i_kicks() {
+    let expected = [
+        (Rotation::East, Rotation::North, [(-1, 0),(-2, 0),(1, 0),(-2, -2),(1, 1)]),
+        (Rotation::East, Rotation::South, [(0, -1),(-1, -1),(2, -1),(-1, 1),(2, -2)]),
+        (Rotation::South, Rotation::East, [(0, 1),(-2, 1),(1, 1),(-2, 2),(1, -1)]),
+        (Rotation::South, Rotation::West, [(-1, 0),(1, 0),(-2, 0),(1, 1),(-2, -2)]),
+        (Rotation::West, Rotation::North, [(0, 1),(1, 1),(-2, 1),(1, -1),(-2, 2)]),
+        (Rotation::West, Rotation::South, [(1, 0),(2, 0),(-1, 0),(2, 2),(-1, -1)]),
+        (Rotation::North, Rotation::East, [(1, 0),(2, 0),(-1, 0),(-1, -1),(2, 2)]),
+        (Rotation::North, Rotation::West, [(0, -1),(-1, -1),(2, -1),(2, -2),(-1, 1)])
+    ];
+    for (from, to, kicks) in expected {
+        assert_eq!(RotationSystem::SRS_PLUS.kicks(Piece::I, from, to).collect::<Vec<_>>(), kicks, "{from:?} -> {to:?}");
+    }
+}
+
+#[test]
+fn no_rotation() {
+    for rs in [RotationSystem::SRS_PLUS, RotationSystem::SRS, RotationSystem::ARS] {
+        for r in ROT {
+            assert_eq!(rs.kicks(Piece::O, r, r.rotate_cw()).count(), 0);
+            assert_eq!(rs.kicks(Piece::T, r, r).count(), 0);
+        }
+    }
+    assert!(!RotationSystem::SRS.can_rotate_180());
+    assert!(try_rotate(&Board::new(), &loc(Piece::T, 4, 5, Rotation::North), Rotation::South, &with_rotation(RotationSystem::SRS)).is_none());
+    assert!(try_rotate(&Board::new(), &loc(Piece::T, 4, 5, Rotation::North), Rotation::South, &with_rotation(RotationSystem::NO_KICK)).is_some());
+}
+
+// ars keeps pieces resting on the same floor instead of kicking them up
+#[test]
+fn ars_floor() {
+    let board = Board::new();
+    let t = loc(Piece::T, 4, 0, Rotation::North);
+
+    let ars = try_rotate(&board, &t, Rotation::East, &with_rotation(RotationSystem::ARS)).unwrap();
+    assert_eq!((ars.x, ars.y), (4, 1));
+    assert_eq!(ars.blocks().iter().map(|b| b.1).min(), Some(0));
+
+    let srs = try_rotate(&board, &t, Rotation::East, &with_rotation(RotationSystem::SRS_PLUS)).unwrap();
+    assert_eq!((srs.x, srs.y), (3, 1));
+
+    // a flat I on the floor can't stand up, ars never kicks it
+    assert!(try_rotate(&board, &loc(Piece::I, 4, 0, Rotation::North), Rotation::East, &with_rotation(RotationSystem::ARS)).is_none());
+    assert!(try_rotate(&board, &loc(Piece::I, 4, 0, Rotation::North), Rotation::East, &with_rotation(RotationSystem::SRS_PLUS)).is_some());
+}
+
+// srs-x 180s can kick two columns sideways, srs+ can't
+#[test]
+fn srs_x_180() {
+    let board = Board::from_ascii("
+        #.#.......
+        ..........
+        ###.######
+    ").unwrap();
+    let t = loc(Piece::T, 1, 1, Rotation::North);
+    assert!(try_rotate(&board, &t, Rotation::South, &with_rotation(RotationSystem::SRS_PLUS)).is_none());
+    let kicked = try_rotate(&board, &t, Rotation::South, &with_rotation(RotationSystem::SRS_X)).unwrap();
+    assert_eq!((kicked.x, kicked.y), (3, 1));
+}
+
+// only srs tables have a kick that upgrades t-spins to full, and it's their last one
+#[test]
+fn full_spin_kick() {
+    for rs in [RotationSystem::SRS, RotationSystem::SRS_PLUS, RotationSystem::SRS_X] {
+        for from in ROT {
+            let to = from.rotate_cw();
+            assert_eq!(rs.full_spin_kick(Piece::T, from, to), Some(rs.kicks(Piece::T, from, to).count() - 1));
+            assert_eq!(rs.full_spin_kick(Piece::T, from, from.rotate_180()), None);
+        }
+    }
+    for rs in [RotationSystem::ARS, RotationSystem::NO_KICK] {
+        assert_eq!(rs.full_spin_kick(Piece::T, Rotation::North, Rotation::East), None);
+    }
+}
@@ -0,0 +1,130 @@
+use utils::{
+    data::{Board, Piece, PieceLocation, Rotation, Spin},
+    game::{Game, GameConfig, SpinRule}
+};
+
+mod common;
+use common::{placements, with_spins};
+
+const RULES: [SpinRule; 4] = [SpinRule::TSpins, SpinRule::AllMini, SpinRule::AllSpin, SpinRule::MiniPlus];
+
+fn spin_at(board: &Board, piece: Piece, spins: SpinRule, x: i8, y: i8, rotation: Rotation) -> Vec<Spin> {
+    placements(board, piece, &with_spins(spins)).iter().filter(|l| (l.x, l.y, l.rotation) == (x, y, rotation)).map(|l| l.spin).collect()
+}
+
+// an S tucked under the overhang can't move in any direction, so its label is whatever the rule makes immobile pieces
+#[test]
+fn immobile_s() {
+    let board = Board::from_ascii("
+        ......###.
+        .####...##
+        ##.#...###
+    ").unwrap();
+    for spins in RULES {
+        assert_eq!(spin_at(&board, Piece::S, spins, 5, 1, Rotation::East), [spins.immobile_spin()], "{spins:?}");
+    }
+}
+
+// a T with only 2 filled corners that is still immobile: nothing under the 3 corner rule, a mini under all-mini+
+#[test]
+fn immobile_t_without_corners() {
+    let board = Board::from_ascii("
+        #...###..#
+        ...##..##.
+        ..##.##.##
+        ..#...#.#.
+        ....###.#.
+    ").unwrap();
+    for spins in RULES {
+        let expected = if spins == SpinRule::MiniPlus { Spin::Mini } else { Spin::None };
+        assert_eq!(spin_at(&board, Piece::T, spins, 1, 3, Rotation::North), [expected], "{spins:?}");
+        assert_eq!(spin_at(&board, Piece::T, spins, 1, 3, Rotation::East), [Spin::None], "{spins:?}");
+    }
+}
+
+// a non-T spin single: no b2b under t spins only, a b2b mini under all-mini, and a full spin's attack under all-spin
+#[test]
+fn advance_classifies_spins() {
+    let board = Board::from_ascii("
+        #.........
+        ....######
+    ").unwrap();
+    let loc = PieceLocation { piece: Piece::I, x: 1, y: 0, rotation: Rotation::North, spin: Spin::Mini };
+    let place = |spins| {
+        let mut game = Game { board, config: with_spins(spins), ..Game::new_empty() };
+        game.advance(Piece::I, &loc)
+    };
+
+    let info = place(SpinRule::TSpins);
+    assert_eq!((info.spin, info.b2b_clear, info.outgoing_attack), (Spin::None, false, 0));
+    for spins in [SpinRule::AllMini, SpinRule::MiniPlus] {
+        let info = place(spins);
+        assert_eq!((info.spin, info.b2b_clear, info.outgoing_attack), (Spin::Mini, true, 1));
+    }
+    let info = place(SpinRule::AllSpin);
+    assert_eq!((info.spin, info.b2b_clear, info.outgoing_attack), (Spin::Full, true, 3));
+}
+
+// the spin movegen finds is the one attack is scored with: a tsd is full under every rule, and the cornerless immobile
+// t clearing a line only sends anything (a b2b mini) under all-mini+
+#[test]
+fn attack_per_rule() {
+    let attack = |board: &Board, spins, loc: (i8, i8, Rotation)| {
+        let found = spin_at(board, Piece::T, spins, loc.0, loc.1, loc.2);
+        assert_eq!(found.len(), 1, "{spins:?}");
+        let mut game = Game { board: *board, config: with_spins(spins), ..Game::new_empty() };
+        let info = game.advance(Piece::T, &PieceLocation { piece: Piece::T, x: loc.0, y: loc.1, rotation: loc.2, spin: found[0] });
+        (info.spin, info.b2b_clear, info.outgoing_attack)
+    };
+
+    let tsd = Board::from_ascii("
+        ###.......
+        ##...#####
+        ###.######
+    ").unwrap();
+    let cornerless = Board::from_ascii("
+        #...###..#
+        ...#######
+        ..##.##.##
+        ..#...#.#.
+        ....###.#.
+    ").unwrap();
+    for spins in RULES {
+        assert_eq!(attack(&tsd, spins, (3, 1, Rotation::South)), (Spin::Full, true, 5), "{spins:?}");
+        let expected = if spins == SpinRule::MiniPlus { (Spin::Mini, true, 1) } else { (Spin::None, false, 0) };
+        assert_eq!(attack(&cornerless, spins, (1, 3, Rotation::North)), expected, "{spins:?}");
+    }
+
+    // minis and normal clears send the same, full spins twice the lines
+    let game = Game::new_empty();
+    for lines in 1..=3 {
+        assert_eq!(game.calculate_attack(lines, Spin::Mini, false, false, 0, 0), game.calculate_attack(lines, Spin::None, false, false, 0, 0));
+        assert_eq!(game.calculate_attack(lines, Spin::Full, false, false, 0, 0), 2 * lines as u16);
+    }
+}
+
+// configs written before spin rules still pick the rule they meant
+#[test]
+fn old_all_spin_config() {
+    let config: GameConfig = serde_json::from_str(r#"{"all_spin": true}"#).unwrap();
+    assert_eq!(config.spins, SpinRule::AllSpin);
+    let config: GameConfig = serde_json::from_str(r#"{"all_spin": false}"#).unwrap();
+    assert_eq!(config.spins, SpinRule::AllMini);
+    // spins wins if both are there
+    let config: GameConfig = serde_json::from_str(r#"{"all_spin": true, "spins": "TSpins"}"#).unwrap();
+    assert_eq!(config.spins, SpinRule::TSpins);
+    let config: GameConfig = serde_json::from_str(&serde_json::to_string(&GameConfig { spins: SpinRule::MiniPlus, ..Default::default() }).unwrap()).unwrap();
+    assert_eq!(config.spins, SpinRule::MiniPlus);
+}
+
+// t spins are labelled by movegen and never relabelled
+#[test]
+fn classify_keeps_t_spins() {
+    for spins in RULES {
+        for spin in [Spin::None, Spin::Mini, Spin::Full] {
+            assert_eq!(spins.classify(Piece::T, spin), spin);
+        }
+        assert_eq!(spins.classify(Piece::L, Spin::None), Spin::None);
+        assert_eq!(spins.classify(Piece::L, Spin::Full), spins.immobile_spin());
+    }
+}